@@ -0,0 +1,806 @@
+//! Client for the standard Beacon API. Works with any consensus client, the few differences
+//! between them are smoothed over here.
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        RwLock,
+    },
+};
+
+use reqwest::Client;
+use serde::{de::DeserializeOwned, Deserialize};
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::{
+    env::DEFAULT_PAIR,
+    metrics,
+    node_client::{ClientSettings, NodeClientError},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BeaconClientKind {
+    Grandine,
+    Lighthouse,
+    Lodestar,
+    Nimbus,
+    Prysm,
+    Teku,
+    Unknown,
+}
+
+impl BeaconClientKind {
+    pub fn parse(str: &str) -> Option<Self> {
+        match str.to_lowercase().as_ref() {
+            "grandine" => Some(Self::Grandine),
+            "lighthouse" => Some(Self::Lighthouse),
+            "lodestar" => Some(Self::Lodestar),
+            "nimbus" => Some(Self::Nimbus),
+            "prysm" => Some(Self::Prysm),
+            "teku" => Some(Self::Teku),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for BeaconClientKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BeaconClientKind::Grandine => write!(f, "grandine"),
+            BeaconClientKind::Lighthouse => write!(f, "lighthouse"),
+            BeaconClientKind::Lodestar => write!(f, "lodestar"),
+            BeaconClientKind::Nimbus => write!(f, "nimbus"),
+            BeaconClientKind::Prysm => write!(f, "prysm"),
+            BeaconClientKind::Teku => write!(f, "teku"),
+            BeaconClientKind::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientVersion {
+    pub kind: BeaconClientKind,
+    pub version: String,
+}
+
+impl ClientVersion {
+    /// Clients report something like `Lighthouse/v4.5.0-441fc16/x86_64-linux`.
+    fn parse(raw: &str) -> Self {
+        let mut parts = raw.split('/');
+        let kind = parts
+            .next()
+            .and_then(BeaconClientKind::parse)
+            .unwrap_or(BeaconClientKind::Unknown);
+        let version = parts.next().unwrap_or_default().to_string();
+        Self { kind, version }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionData {
+    version: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Version {
+    data: VersionData,
+}
+
+pub struct BeaconNode {
+    pub node_url: String,
+    /// Name of the node pair this node belongs to, labels its metrics.
+    pub pair: String,
+    client: Client,
+    kind: RwLock<BeaconClientKind>,
+    /// Completed requests of [`BeaconNode::sync_status_shared`], tells waiting callers whether a
+    /// response arrived while they waited.
+    sync_status_requests: AtomicU64,
+    /// The response of the last of those requests, None if it failed.
+    last_sync_status: Mutex<Option<Syncing>>,
+}
+
+impl BeaconNode {
+    pub fn new(node_url: String) -> Self {
+        Self::with_settings(node_url, &ClientSettings::default())
+    }
+
+    pub fn with_settings(node_url: String, settings: &ClientSettings) -> Self {
+        Self {
+            node_url,
+            pair: DEFAULT_PAIR.to_string(),
+            client: settings.build(),
+            kind: RwLock::new(BeaconClientKind::Unknown),
+            sync_status_requests: AtomicU64::new(0),
+            last_sync_status: Mutex::new(None),
+        }
+    }
+
+    pub fn with_pair(mut self, pair: impl Into<String>) -> Self {
+        self.pair = pair.into();
+        self
+    }
+
+    /// Which consensus client we're talking to, used to name it in logs and metrics.
+    pub fn kind(&self) -> BeaconClientKind {
+        *self.kind.read().unwrap()
+    }
+
+    pub fn set_kind(&self, kind: BeaconClientKind) {
+        *self.kind.write().unwrap() = kind;
+    }
+
+    pub async fn client_version(&self) -> Result<ClientVersion, NodeClientError> {
+        let version: Version = self.get("/eth/v1/node/version").await?;
+        Ok(ClientVersion::parse(&version.data.version))
+    }
+
+    /// Asks the node for its client and version. An explicitly `configured` client wins over what
+    /// the node reports, also when it doesn't answer.
+    pub async fn detect_kind(
+        &self,
+        configured: Option<BeaconClientKind>,
+    ) -> Result<ClientVersion, NodeClientError> {
+        if let Some(kind) = configured {
+            self.set_kind(kind);
+        }
+        let mut client_version = self.client_version().await?;
+        if let Some(kind) = configured {
+            client_version.kind = kind;
+        }
+        self.set_kind(client_version.kind);
+        metrics::BEACON_CLIENT_INFO
+            .with_label_values(&[
+                &self.pair,
+                &client_version.kind.to_string(),
+                &client_version.version,
+            ])
+            .set(1);
+        Ok(client_version)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, NodeClientError> {
+        self.get_labelled(path, path).await
+    }
+
+    /// Errors are counted by `endpoint`, which unlike `path` mustn't contain ids.
+    async fn get_labelled<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        endpoint: &str,
+    ) -> Result<T, NodeClientError> {
+        let url = format!("{}{path}", &self.node_url);
+        let result: Result<T, NodeClientError> = async {
+            let res = self.client.get(url).send().await?;
+            let res = NodeClientError::check_status(res).await?;
+            let body: T = res.json().await?;
+            Ok(body)
+        }
+        .await;
+
+        if let Err(e) = &result {
+            metrics::REQUEST_ERRORS
+                .with_label_values(&[&self.pair, "beacon_node", endpoint, e.kind()])
+                .inc();
+        }
+
+        result
+    }
+
+    pub async fn genesis(&self) -> Result<Genesis, NodeClientError> {
+        self.get("/eth/v1/beacon/genesis").await
+    }
+
+    pub async fn sync_status(&self) -> Result<Syncing, NodeClientError> {
+        self.get("/eth/v1/node/syncing").await
+    }
+
+    /// Like [`BeaconNode::sync_status`], but callers arriving while a request is in flight get
+    /// its response, so checks evaluated together make a single request. Nothing is kept for
+    /// later callers, and errors aren't shared.
+    pub async fn sync_status_shared(&self) -> Result<Syncing, NodeClientError> {
+        let requests = self.sync_status_requests.load(Ordering::Acquire);
+        let mut last_sync_status = self.last_sync_status.lock().await;
+        if self.sync_status_requests.load(Ordering::Acquire) != requests {
+            if let Some(sync_status) = last_sync_status.as_ref() {
+                return Ok(sync_status.clone());
+            }
+        }
+        let result = self.sync_status().await;
+        *last_sync_status = result.as_ref().ok().cloned();
+        self.sync_status_requests.fetch_add(1, Ordering::Release);
+        result
+    }
+
+    pub async fn peer_counts(&self) -> Result<PeerCounts, NodeClientError> {
+        self.get("/eth/v1/node/peer_count").await
+    }
+
+    pub async fn head_header(&self) -> Result<HeadHeader, NodeClientError> {
+        self.get("/eth/v1/beacon/headers/head").await
+    }
+
+    /// The canonical header at `slot`, None when no block was proposed in it.
+    pub async fn header_at_slot(&self, slot: u64) -> Result<Option<HeadHeader>, NodeClientError> {
+        let result = self
+            .get_labelled(
+                &format!("/eth/v1/beacon/headers/{slot}"),
+                "/eth/v1/beacon/headers/{slot}",
+            )
+            .await;
+        match result {
+            Ok(header) => Ok(Some(header)),
+            Err(NodeClientError::Status { status: 404, .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The blinded head block, which carries the execution payload header instead of the full
+    /// payload with all its transactions.
+    pub async fn head_block(&self) -> Result<HeadBlock, NodeClientError> {
+        self.get("/eth/v1/beacon/blinded_blocks/head").await
+    }
+
+    pub async fn finality_checkpoints(&self) -> Result<FinalityCheckpoints, NodeClientError> {
+        self.get("/eth/v1/beacon/states/head/finality_checkpoints")
+            .await
+    }
+
+    pub async fn ping_ok(&self) -> anyhow::Result<bool> {
+        let url = format!("{}/eth/v1/node/version", &self.node_url);
+        let res = self.client.get(url).send().await;
+        match res {
+            Ok(res) => Ok(res.status().is_success()),
+            Err(e) => {
+                debug!("{} ping failed: {}", self.kind(), e);
+                Ok(false)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SyncingData {
+    /// Not every client (version) reports this.
+    #[serde(default)]
+    el_offline: Option<bool>,
+    #[serde(deserialize_with = "deserialize_u64_from_string")]
+    head_slot: u64,
+    /// Older clients don't report this, they predate optimistic sync.
+    #[serde(default)]
+    is_optimistic: bool,
+    is_syncing: bool,
+    #[serde(deserialize_with = "deserialize_u64_from_string")]
+    sync_distance: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Syncing {
+    data: SyncingData,
+}
+
+impl Syncing {
+    pub fn is_syncing(&self) -> bool {
+        self.data.is_syncing
+    }
+
+    pub fn head_slot(&self) -> u64 {
+        self.data.head_slot
+    }
+
+    pub fn is_optimistic(&self) -> bool {
+        self.data.is_optimistic
+    }
+
+    /// None when the client does not report it.
+    pub fn is_el_offline(&self) -> Option<bool> {
+        self.data.el_offline
+    }
+
+    // Sync distance will be > 0 when a node restarts and is catching up __even if it is not syncing__.
+    pub fn sync_distance(&self) -> u64 {
+        self.data.sync_distance
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrNumber {
+    String(String),
+    Number(u64),
+}
+
+/// The spec says quoted integers, some clients send plain numbers for some fields.
+pub(crate) fn deserialize_u64_from_string<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match StringOrNumber::deserialize(deserializer)? {
+        StringOrNumber::String(s) => s.parse::<u64>().map_err(serde::de::Error::custom),
+        StringOrNumber::Number(number) => Ok(number),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GenesisData {
+    #[serde(deserialize_with = "deserialize_u64_from_string")]
+    genesis_time: u64,
+    genesis_validators_root: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Genesis {
+    data: GenesisData,
+}
+
+impl Genesis {
+    pub fn genesis_time(&self) -> u64 {
+        self.data.genesis_time
+    }
+
+    pub fn genesis_validators_root(&self) -> &str {
+        &self.data.genesis_validators_root
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PeerCountsData {
+    #[serde(deserialize_with = "deserialize_u64_from_string")]
+    connected: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PeerCounts {
+    data: PeerCountsData,
+}
+
+impl PeerCounts {
+    pub fn peer_count(&self) -> u64 {
+        self.data.connected
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct HeaderMessage {
+    #[serde(deserialize_with = "deserialize_u64_from_string")]
+    slot: u64,
+    parent_root: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SignedHeader {
+    message: HeaderMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct HeadHeaderData {
+    root: String,
+    header: SignedHeader,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HeadHeader {
+    data: HeadHeaderData,
+}
+
+impl HeadHeader {
+    pub fn slot(&self) -> u64 {
+        self.data.header.message.slot
+    }
+
+    pub fn root(&self) -> &str {
+        &self.data.root
+    }
+
+    pub fn parent_root(&self) -> &str {
+        &self.data.header.message.parent_root
+    }
+}
+
+/// The execution block a beacon block commits to.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ExecutionBlockRef {
+    pub block_hash: String,
+    #[serde(deserialize_with = "deserialize_u64_from_string")]
+    pub block_number: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockBody {
+    /// Blocks from before the merge have none.
+    #[serde(default)]
+    execution_payload_header: Option<ExecutionBlockRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockMessage {
+    #[serde(deserialize_with = "deserialize_u64_from_string")]
+    slot: u64,
+    body: BlockBody,
+}
+
+#[derive(Debug, Deserialize)]
+struct SignedBlock {
+    message: BlockMessage,
+}
+
+/// The few fields of the head block we care about, the rest of the body is ignored.
+#[derive(Debug, Deserialize)]
+pub struct HeadBlock {
+    data: SignedBlock,
+}
+
+impl HeadBlock {
+    pub fn slot(&self) -> u64 {
+        self.data.message.slot
+    }
+
+    pub fn execution_payload(&self) -> Option<&ExecutionBlockRef> {
+        self.data.message.body.execution_payload_header.as_ref()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Checkpoint {
+    #[serde(deserialize_with = "deserialize_u64_from_string")]
+    pub epoch: u64,
+    pub root: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct FinalityCheckpointsData {
+    previous_justified: Checkpoint,
+    current_justified: Checkpoint,
+    finalized: Checkpoint,
+}
+
+/// The justified and finalized checkpoints of the head state.
+#[derive(Debug, Deserialize)]
+pub struct FinalityCheckpoints {
+    data: FinalityCheckpointsData,
+}
+
+impl FinalityCheckpoints {
+    pub fn previous_justified(&self) -> &Checkpoint {
+        &self.data.previous_justified
+    }
+
+    pub fn current_justified(&self) -> &Checkpoint {
+        &self.data.current_justified
+    }
+
+    pub fn finalized(&self) -> &Checkpoint {
+        &self.data.finalized
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{env::DEFAULT_PAIR, metrics};
+
+    use super::{BeaconClientKind, BeaconNode, NodeClientError};
+
+    #[test]
+    fn decode_peer_counts() {
+        let json = json!({
+            "data": {
+                "connected": "87",
+                "connecting": "0",
+                "disconnected": "719",
+                "disconnecting": "0"
+            }
+        });
+        let health: super::PeerCounts = serde_json::from_value(json).unwrap();
+        assert_eq!(health.data.connected, 87);
+    }
+
+    #[test]
+    fn decode_syncing() {
+        let json = json!({
+            "data": {
+                "el_offline": false,
+                "head_slot": "5478944",
+                "is_optimistic": false,
+                "is_syncing": false,
+                "sync_distance": "0"
+            }
+        });
+        let health: super::Syncing = serde_json::from_value(json).unwrap();
+        assert!(!health.is_syncing());
+        assert!(!health.is_optimistic());
+        assert_eq!(health.is_el_offline(), Some(false));
+        assert_eq!(health.head_slot(), 5478944);
+    }
+
+    #[test]
+    fn decode_syncing_without_optional_fields() {
+        let json = json!({
+            "data": {
+                "head_slot": 5478944,
+                "is_syncing": false,
+                "sync_distance": "0"
+            }
+        });
+        let health: super::Syncing = serde_json::from_value(json).unwrap();
+        assert!(!health.is_optimistic());
+        assert_eq!(health.is_el_offline(), None);
+        assert_eq!(health.head_slot(), 5478944);
+    }
+
+    #[test]
+    fn decode_head_block() {
+        let json = json!({
+            "version": "deneb",
+            "execution_optimistic": false,
+            "finalized": false,
+            "data": {
+                "message": {
+                    "slot": "8000000",
+                    "proposer_index": "1",
+                    "body": {
+                        "graffiti": "0x00",
+                        "execution_payload_header": {
+                            "block_hash": "0xabc",
+                            "block_number": "19531250",
+                            "transactions_root": "0x00"
+                        }
+                    }
+                },
+                "signature": "0x00"
+            }
+        });
+        let block: super::HeadBlock = serde_json::from_value(json).unwrap();
+        assert_eq!(block.slot(), 8000000);
+        let payload = block.execution_payload().unwrap();
+        assert_eq!(payload.block_hash, "0xabc");
+        assert_eq!(payload.block_number, 19531250);
+
+        let json = json!({
+            "data": { "message": { "slot": "1", "body": { "graffiti": "0x00" } } }
+        });
+        let block: super::HeadBlock = serde_json::from_value(json).unwrap();
+        assert_eq!(block.execution_payload(), None);
+    }
+
+    #[test]
+    fn decode_finality_checkpoints() {
+        let json = json!({
+            "data": {
+                "previous_justified": { "epoch": "250000", "root": "0xaa" },
+                "current_justified": { "epoch": "250001", "root": "0xbb" },
+                "finalized": { "epoch": "250000", "root": "0xaa" }
+            }
+        });
+        let checkpoints: super::FinalityCheckpoints = serde_json::from_value(json).unwrap();
+        assert_eq!(checkpoints.previous_justified().epoch, 250000);
+        assert_eq!(checkpoints.current_justified().epoch, 250001);
+        assert_eq!(checkpoints.finalized().epoch, 250000);
+        assert_eq!(checkpoints.finalized().root, "0xaa");
+    }
+
+    #[test]
+    fn parse_client_version() {
+        let version = super::ClientVersion::parse("Lighthouse/v4.5.0-441fc16/x86_64-linux");
+        assert_eq!(version.kind, BeaconClientKind::Lighthouse);
+        assert_eq!(version.version, "v4.5.0-441fc16");
+
+        let version = super::ClientVersion::parse(
+            "teku/v23.10.0/linux-x86_64/-eclipseadoptium-openjdk64bitservervm-java-17",
+        );
+        assert_eq!(version.kind, BeaconClientKind::Teku);
+
+        let version = super::ClientVersion::parse("Nimbus/v23.11.0-8c4ee3-stateofus");
+        assert_eq!(version.kind, BeaconClientKind::Nimbus);
+
+        let version = super::ClientVersion::parse("something-else");
+        assert_eq!(version.kind, BeaconClientKind::Unknown);
+    }
+
+    #[tokio::test]
+    async fn test_detect_kind() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/eth/v1/node/version")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({ "data": { "version": "Prysm/v4.1.1/linux-amd64" } }).to_string())
+            .expect(2)
+            .create_async()
+            .await;
+
+        let beacon_node = BeaconNode::new(server.url());
+        assert_eq!(beacon_node.kind(), BeaconClientKind::Unknown);
+
+        let version = beacon_node.detect_kind(None).await.unwrap();
+
+        assert_eq!(version.kind, BeaconClientKind::Prysm);
+        assert_eq!(beacon_node.kind(), BeaconClientKind::Prysm);
+
+        let version = beacon_node
+            .detect_kind(Some(BeaconClientKind::Lighthouse))
+            .await
+            .unwrap();
+
+        assert_eq!(version.kind, BeaconClientKind::Lighthouse);
+        assert_eq!(version.version, "v4.1.1");
+        assert_eq!(beacon_node.kind(), BeaconClientKind::Lighthouse);
+        assert_eq!(
+            metrics::BEACON_CLIENT_INFO
+                .with_label_values(&[DEFAULT_PAIR, "lighthouse", "v4.1.1"])
+                .get(),
+            1
+        );
+        mock.assert_async().await;
+    }
+
+    #[test]
+    fn decode_head_header() {
+        let json = json!({
+            "execution_optimistic": false,
+            "finalized": false,
+            "data": {
+                "root": "0x9f2b4c3a0a6e1e2c7a1d8c5b0f3e4d2a1b0c9d8e7f6a5b4c3d2e1f0a9b8c7d6e",
+                "canonical": true,
+                "header": {
+                    "message": {
+                        "slot": "8000000",
+                        "proposer_index": "1",
+                        "parent_root": "0x1",
+                        "state_root": "0x2",
+                        "body_root": "0x3"
+                    },
+                    "signature": "0x4"
+                }
+            }
+        });
+        let header: super::HeadHeader = serde_json::from_value(json).unwrap();
+        assert_eq!(header.slot(), 8_000_000);
+        assert!(header.root().starts_with("0x9f2b"));
+        assert_eq!(header.parent_root(), "0x1");
+    }
+
+    #[tokio::test]
+    async fn test_ping_ok() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/eth/v1/node/version")
+            .with_status(200)
+            .create_async()
+            .await;
+
+        let beacon_node = BeaconNode::new(server.url());
+        let ping_ok = beacon_node.ping_ok().await.unwrap();
+
+        assert!(ping_ok);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_peer_counts() {
+        let mut server = mockito::Server::new_async().await;
+        let peer_count_response = json!({
+            "data": {
+                "connected": "87",
+                "connecting": "0",
+                "disconnected": "719",
+                "disconnecting": "0"
+            }
+        });
+        let mock = server
+            .mock("GET", "/eth/v1/node/peer_count")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&peer_count_response).unwrap())
+            .create_async()
+            .await;
+
+        let beacon_node = BeaconNode::new(server.url());
+        let peer_counts = beacon_node.peer_counts().await.unwrap();
+
+        assert_eq!(peer_counts.peer_count(), 87);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_sync_status() {
+        let mut server = mockito::Server::new_async().await;
+        let sync_status_response = json!({
+            "data": {
+                "el_offline": false,
+                "head_slot": "5478944",
+                "is_optimistic": false,
+                "is_syncing": false,
+                "sync_distance": "0"
+            }
+        });
+        let mock = server
+            .mock("GET", "/eth/v1/node/syncing")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_string(&sync_status_response).unwrap())
+            .create_async()
+            .await;
+
+        let beacon_node = BeaconNode::new(server.url());
+        let sync_status = beacon_node.sync_status().await.unwrap();
+
+        assert!(!sync_status.is_syncing());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_sync_status_shared() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/eth/v1/node/syncing")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "data": {
+                        "head_slot": "5478944",
+                        "is_optimistic": false,
+                        "is_syncing": false,
+                        "sync_distance": "0"
+                    }
+                })
+                .to_string(),
+            )
+            .expect(2)
+            .create_async()
+            .await;
+
+        let beacon_node = BeaconNode::new(server.url());
+        let (first, second, third) = tokio::join!(
+            beacon_node.sync_status_shared(),
+            beacon_node.sync_status_shared(),
+            beacon_node.sync_status_shared()
+        );
+        assert!(first.is_ok() && second.is_ok() && third.is_ok());
+
+        // Later callers get a fresh response.
+        assert!(beacon_node.sync_status_shared().await.is_ok());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_sync_status_shared_errors() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/eth/v1/node/syncing")
+            .with_status(503)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let beacon_node = BeaconNode::new(server.url());
+        let (first, second) = tokio::join!(
+            beacon_node.sync_status_shared(),
+            beacon_node.sync_status_shared()
+        );
+
+        // Each caller sees its own error.
+        assert!(first.is_err() && second.is_err());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_sync_status_error_status() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/eth/v1/node/syncing")
+            .with_status(503)
+            .with_body(r#"{"code":503,"message":"Node is syncing"}"#)
+            .create_async()
+            .await;
+
+        let beacon_node = BeaconNode::new(server.url());
+        let error = beacon_node.sync_status().await.unwrap_err();
+
+        assert!(matches!(error, NodeClientError::Status { status: 503, .. }));
+        assert!(error.to_string().contains("Node is syncing"));
+    }
+}
@@ -0,0 +1,1027 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use serde_json::json;
+use tracing::debug;
+
+use crate::{
+    beacon_node::{BeaconNode, Checkpoint},
+    clock::{self, SECONDS_PER_SLOT},
+    env::Network,
+    events::BeaconEvents,
+    health_check::{CheckOutcome, HealthCheck},
+    metrics,
+    node_client::NodeClientError,
+    reorg::{Reorg, ReorgTracker},
+};
+
+pub struct BeaconNodePeerCountCheck {
+    beacon_node: Arc<BeaconNode>,
+    min_peer_count: u64,
+}
+
+impl BeaconNodePeerCountCheck {
+    pub fn new(beacon_node: Arc<BeaconNode>, min_peer_count: u64) -> Self {
+        Self {
+            beacon_node,
+            min_peer_count,
+        }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for BeaconNodePeerCountCheck {
+    fn name(&self) -> &'static str {
+        "beacon_node_peer_count"
+    }
+
+    async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+        let peer_count = self.beacon_node.peer_counts().await?.peer_count();
+        metrics::BEACON_PEER_COUNT
+            .with_label_values(&[&self.beacon_node.pair])
+            .set(peer_count as i64);
+        let outcome = if peer_count < self.min_peer_count {
+            CheckOutcome::fail(format!(
+                "{} has {peer_count} peers, less than {}",
+                self.beacon_node.kind(),
+                self.min_peer_count
+            ))
+        } else {
+            CheckOutcome::pass()
+        };
+        Ok(outcome.with_observed(peer_count))
+    }
+}
+
+pub struct BeaconNodeSyncingCheck {
+    beacon_node: Arc<BeaconNode>,
+}
+
+impl BeaconNodeSyncingCheck {
+    pub fn new(beacon_node: Arc<BeaconNode>) -> Self {
+        Self { beacon_node }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for BeaconNodeSyncingCheck {
+    fn name(&self) -> &'static str {
+        "beacon_node_syncing"
+    }
+
+    async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+        let syncing = self.beacon_node.sync_status_shared().await?.is_syncing();
+        metrics::BEACON_SYNCING
+            .with_label_values(&[&self.beacon_node.pair])
+            .set(i64::from(syncing));
+        let outcome = if syncing {
+            CheckOutcome::fail(format!("{} is syncing", self.beacon_node.kind()))
+        } else {
+            CheckOutcome::pass()
+        };
+        Ok(outcome.with_observed(syncing))
+    }
+}
+
+pub struct BeaconNodeOptimisticCheck {
+    beacon_node: Arc<BeaconNode>,
+}
+
+impl BeaconNodeOptimisticCheck {
+    pub fn new(beacon_node: Arc<BeaconNode>) -> Self {
+        Self { beacon_node }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for BeaconNodeOptimisticCheck {
+    fn name(&self) -> &'static str {
+        "beacon_node_optimistic"
+    }
+
+    async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+        let optimistic = self.beacon_node.sync_status_shared().await?.is_optimistic();
+        metrics::BEACON_OPTIMISTIC
+            .with_label_values(&[&self.beacon_node.pair])
+            .set(i64::from(optimistic));
+        let outcome = if optimistic {
+            CheckOutcome::fail(format!("{} sync is optimistic", self.beacon_node.kind()))
+        } else {
+            CheckOutcome::pass()
+        };
+        Ok(outcome.with_observed(optimistic))
+    }
+}
+
+pub struct BeaconNodeElOfflineCheck {
+    beacon_node: Arc<BeaconNode>,
+}
+
+impl BeaconNodeElOfflineCheck {
+    pub fn new(beacon_node: Arc<BeaconNode>) -> Self {
+        Self { beacon_node }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for BeaconNodeElOfflineCheck {
+    fn name(&self) -> &'static str {
+        "beacon_node_el_offline"
+    }
+
+    async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+        let Some(el_offline) = self.beacon_node.sync_status_shared().await?.is_el_offline() else {
+            debug!("{} does not report el_offline", self.beacon_node.kind());
+            return Ok(CheckOutcome::pass());
+        };
+        metrics::BEACON_EL_OFFLINE
+            .with_label_values(&[&self.beacon_node.pair])
+            .set(i64::from(el_offline));
+        let outcome = if el_offline {
+            CheckOutcome::fail(format!("{} says el is offline", self.beacon_node.kind()))
+        } else {
+            CheckOutcome::pass()
+        };
+        Ok(outcome.with_observed(el_offline))
+    }
+}
+
+pub struct BeaconNodeSyncDistanceCheck {
+    beacon_node: Arc<BeaconNode>,
+    max_sync_distance: u64,
+}
+
+impl BeaconNodeSyncDistanceCheck {
+    pub fn new(beacon_node: Arc<BeaconNode>, max_sync_distance: u64) -> Self {
+        Self {
+            beacon_node,
+            max_sync_distance,
+        }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for BeaconNodeSyncDistanceCheck {
+    fn name(&self) -> &'static str {
+        "beacon_node_sync_distance"
+    }
+
+    async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+        let sync_distance = self.beacon_node.sync_status_shared().await?.sync_distance();
+        metrics::BEACON_SYNC_DISTANCE
+            .with_label_values(&[&self.beacon_node.pair])
+            .set(sync_distance as i64);
+        let outcome = if sync_distance > self.max_sync_distance {
+            CheckOutcome::fail(format!(
+                "{} sync distance is {sync_distance}, more than {}",
+                self.beacon_node.kind(),
+                self.max_sync_distance
+            ))
+        } else {
+            CheckOutcome::pass()
+        };
+        Ok(outcome.with_observed(sync_distance))
+    }
+}
+
+/// Compares the genesis validators root of `/eth/v1/beacon/genesis` with the one of the
+/// configured `NETWORK`, so a beacon node following another chain can't pass as ready.
+pub struct BeaconNodeGenesisCheck {
+    beacon_node: Arc<BeaconNode>,
+    network: Network,
+}
+
+impl BeaconNodeGenesisCheck {
+    pub fn new(beacon_node: Arc<BeaconNode>, network: Network) -> Self {
+        Self {
+            beacon_node,
+            network,
+        }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for BeaconNodeGenesisCheck {
+    fn name(&self) -> &'static str {
+        "beacon_node_genesis"
+    }
+
+    async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+        let genesis = self.beacon_node.genesis().await?;
+        let root = genesis.genesis_validators_root();
+        let expected = self.network.genesis_validators_root();
+        let outcome = if !root.eq_ignore_ascii_case(expected) {
+            CheckOutcome::fail(format!(
+                "{} has genesis validators root {root}, expected {expected} for {}, check NETWORK and BEACON_URL",
+                self.beacon_node.kind(),
+                self.network
+            ))
+        } else {
+            CheckOutcome::pass()
+        };
+        Ok(outcome.with_observed(root))
+    }
+}
+
+/// Sync distance is computed by the node itself, comparing the head slot against our own wall
+/// clock also catches clock drift and a stuck fork choice.
+pub struct BeaconNodeHeadFreshnessCheck {
+    beacon_node: Arc<BeaconNode>,
+    network: Network,
+    max_lag_slots: u64,
+}
+
+impl BeaconNodeHeadFreshnessCheck {
+    pub fn new(beacon_node: Arc<BeaconNode>, network: Network, max_lag_slots: u64) -> Self {
+        Self {
+            beacon_node,
+            network,
+            max_lag_slots,
+        }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for BeaconNodeHeadFreshnessCheck {
+    fn name(&self) -> &'static str {
+        "beacon_node_head_freshness"
+    }
+
+    async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+        let head_slot = self.beacon_node.head_header().await?.slot();
+        let wall_clock_slot = clock::current_slot(&self.network);
+        let lag_slots = wall_clock_slot.saturating_sub(head_slot);
+        metrics::BEACON_HEAD_SLOT
+            .with_label_values(&[&self.beacon_node.pair])
+            .set(head_slot as i64);
+        metrics::BEACON_HEAD_LAG_SLOTS
+            .with_label_values(&[&self.beacon_node.pair])
+            .set(lag_slots as i64);
+
+        let outcome = if lag_slots > self.max_lag_slots {
+            CheckOutcome::fail(format!(
+                "{} head slot {head_slot} is {lag_slots} slots behind wall clock slot {wall_clock_slot}, more than {}",
+                self.beacon_node.kind(),
+                self.max_lag_slots
+            ))
+        } else {
+            CheckOutcome::pass()
+        };
+        Ok(outcome.with_observed(json!({
+            "head_slot": head_slot,
+            "wall_clock_slot": wall_clock_slot,
+            "lag_slots": lag_slots,
+        })))
+    }
+}
+
+/// A node following a branch that doesn't finalize, or whose finalized checkpoint is far behind
+/// the network, can look perfectly synced otherwise.
+pub struct BeaconNodeFinalityCheck {
+    beacon_node: Arc<BeaconNode>,
+    network: Network,
+    max_finality_distance_epochs: u64,
+    events: Option<Arc<BeaconEvents>>,
+}
+
+impl BeaconNodeFinalityCheck {
+    pub fn new(
+        beacon_node: Arc<BeaconNode>,
+        network: Network,
+        max_finality_distance_epochs: u64,
+    ) -> Self {
+        Self {
+            beacon_node,
+            network,
+            max_finality_distance_epochs,
+            events: None,
+        }
+    }
+
+    /// Takes the finalized checkpoint from `finalized_checkpoint` events while the stream is up,
+    /// instead of polling for it.
+    pub fn with_events(mut self, events: Arc<BeaconEvents>) -> Self {
+        self.events = Some(events);
+        self
+    }
+}
+
+#[async_trait]
+impl HealthCheck for BeaconNodeFinalityCheck {
+    fn name(&self) -> &'static str {
+        "beacon_node_finality"
+    }
+
+    async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+        let current_epoch = clock::current_epoch(&self.network);
+        // A fresh event is as good as a poll, but we poll before failing on it.
+        let event = self
+            .events
+            .as_ref()
+            .and_then(|events| events.last_finalized())
+            .filter(|finalized| {
+                current_epoch.saturating_sub(finalized.epoch) <= self.max_finality_distance_epochs
+            });
+        let (finalized, justified_epoch, source) = match event {
+            Some(finalized) => (
+                Checkpoint {
+                    epoch: finalized.epoch,
+                    root: finalized.block,
+                },
+                None,
+                "event",
+            ),
+            None => {
+                let checkpoints = self.beacon_node.finality_checkpoints().await?;
+                (
+                    checkpoints.finalized().clone(),
+                    Some(checkpoints.current_justified().epoch),
+                    "poll",
+                )
+            }
+        };
+        let finality_distance = current_epoch.saturating_sub(finalized.epoch);
+        metrics::BEACON_FINALIZED_EPOCH
+            .with_label_values(&[&self.beacon_node.pair])
+            .set(finalized.epoch as i64);
+        metrics::BEACON_FINALITY_DISTANCE_EPOCHS
+            .with_label_values(&[&self.beacon_node.pair])
+            .set(finality_distance as i64);
+
+        let outcome = if finality_distance > self.max_finality_distance_epochs {
+            CheckOutcome::fail(format!(
+                "{} finalized epoch {} is {finality_distance} epochs behind wall clock epoch {current_epoch}, more than {}",
+                self.beacon_node.kind(),
+                finalized.epoch,
+                self.max_finality_distance_epochs
+            ))
+        } else {
+            CheckOutcome::pass()
+        };
+        Ok(outcome.with_observed(json!({
+            "current_epoch": current_epoch,
+            "justified_epoch": justified_epoch,
+            "finalized_epoch": finalized.epoch,
+            "finalized_root": finalized.root,
+            "finality_distance": finality_distance,
+            "source": source,
+        })))
+    }
+}
+
+/// Beacon nodes emit a head event every slot that has a block, a stream gone quiet means the node
+/// stopped following the chain, even while its REST API still answers.
+pub struct BeaconNodeHeadEventsCheck {
+    events: Arc<BeaconEvents>,
+    max_slots_without_head: u64,
+}
+
+impl BeaconNodeHeadEventsCheck {
+    pub fn new(events: Arc<BeaconEvents>, max_slots_without_head: u64) -> Self {
+        Self {
+            events,
+            max_slots_without_head,
+        }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for BeaconNodeHeadEventsCheck {
+    fn name(&self) -> &'static str {
+        "beacon_node_head_events"
+    }
+
+    async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+        let kind = self.events.beacon_node().kind();
+        let Some(since_head) = self.events.since_head() else {
+            return Ok(CheckOutcome::warn(format!(
+                "{kind} event stream is down, relying on polling"
+            ))
+            .with_observed(json!({ "connected": false })));
+        };
+
+        let max_since_head = Duration::from_secs(self.max_slots_without_head * SECONDS_PER_SLOT);
+        let outcome = if since_head > max_since_head {
+            CheckOutcome::fail(format!(
+                "{kind} sent no head event for {}s, more than {} slots",
+                since_head.as_secs(),
+                self.max_slots_without_head
+            ))
+        } else {
+            CheckOutcome::pass()
+        };
+        Ok(outcome.with_observed(json!({
+            "connected": true,
+            "head_slot": self.events.last_head().map(|head| head.slot),
+            "secs_since_head": since_head.as_secs(),
+        })))
+    }
+}
+
+/// How many heads of earlier polls we remember to find where a reorg forked off.
+const MAX_TRACKED_HEADS: usize = 64;
+
+/// Notices reorgs between polls, unless the event stream reports them, and with `max_depth` fails
+/// while a reorg deeper than it is recent.
+pub struct BeaconNodeReorgCheck {
+    beacon_node: Arc<BeaconNode>,
+    reorgs: Arc<ReorgTracker>,
+    events: Option<Arc<BeaconEvents>>,
+    max_depth: Option<u64>,
+    /// Slot and root of the heads earlier polls saw, oldest first.
+    heads: Mutex<VecDeque<(u64, String)>>,
+}
+
+impl BeaconNodeReorgCheck {
+    pub fn new(
+        beacon_node: Arc<BeaconNode>,
+        reorgs: Arc<ReorgTracker>,
+        max_depth: Option<u64>,
+    ) -> Self {
+        Self {
+            beacon_node,
+            reorgs,
+            events: None,
+            max_depth,
+            heads: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Leaves detecting reorgs to the event stream while it is connected.
+    pub fn with_events(mut self, events: Arc<BeaconEvents>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    async fn detect(&self) -> Result<(), NodeClientError> {
+        let head = self.beacon_node.head_header().await?;
+        let heads: Vec<(u64, String)> = self.heads.lock().unwrap().iter().cloned().collect();
+
+        let mut ancestor_slot = None;
+        let reorg = match heads.last() {
+            None => None,
+            Some((_, root)) if root == head.root() || root == head.parent_root() => None,
+            Some((last_slot, last_root)) => {
+                // Heads further back than max_depth can't make a reorg pass, so we don't look.
+                let oldest_slot = self
+                    .max_depth
+                    .map_or(0, |max_depth| last_slot.saturating_sub(max_depth));
+                let candidates: Vec<&(u64, String)> = heads
+                    .iter()
+                    .rev()
+                    .take_while(|(slot, _)| *slot >= oldest_slot)
+                    .collect();
+                ancestor_slot = self.newest_canonical(&candidates).await?;
+                match ancestor_slot {
+                    // More than one block since the last poll, no reorg.
+                    Some(slot) if slot == *last_slot => None,
+                    // Without a common ancestor the reorg is at least as deep as what we checked.
+                    _ => {
+                        let checked_from = if candidates.len() < heads.len() {
+                            oldest_slot
+                        } else {
+                            heads[0].0
+                        };
+                        Some(Reorg {
+                            slot: head.slot(),
+                            depth: last_slot
+                                - ancestor_slot.unwrap_or_else(|| checked_from.saturating_sub(1)),
+                            old_head_block: last_root.clone(),
+                            new_head_block: head.root().to_string(),
+                            source: "poll",
+                        })
+                    }
+                }
+            }
+        };
+
+        let mut heads = self.heads.lock().unwrap();
+        if let Some(reorg) = reorg {
+            heads.retain(|(slot, _)| ancestor_slot.is_some_and(|ancestor| *slot <= ancestor));
+            self.reorgs.record(reorg);
+        }
+        if heads.back().is_none_or(|(_, root)| root != head.root()) {
+            heads.push_back((head.slot(), head.root().to_string()));
+        }
+        while heads.len() > MAX_TRACKED_HEADS {
+            heads.pop_front();
+        }
+        Ok(())
+    }
+
+    /// The slot of the newest of `candidates`, given newest first, that is still canonical.
+    async fn newest_canonical(
+        &self,
+        candidates: &[&(u64, String)],
+    ) -> Result<Option<u64>, NodeClientError> {
+        let Some(((newest_slot, newest_root), older)) = candidates.split_first() else {
+            return Ok(None);
+        };
+        // Usually it is the newest, more than one block came since the last poll. After a reorg
+        // the older ones are fetched at once, rather than one after another.
+        if self.is_canonical(*newest_slot, newest_root).await? {
+            return Ok(Some(*newest_slot));
+        }
+        let canonical = join_all(
+            older
+                .iter()
+                .map(|(slot, root)| self.is_canonical(*slot, root)),
+        )
+        .await;
+        for ((slot, _), canonical) in older.iter().zip(canonical) {
+            if canonical? {
+                return Ok(Some(*slot));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn is_canonical(&self, slot: u64, root: &str) -> Result<bool, NodeClientError> {
+        let header = self.beacon_node.header_at_slot(slot).await?;
+        Ok(header.is_some_and(|header| header.root() == root))
+    }
+}
+
+#[async_trait]
+impl HealthCheck for BeaconNodeReorgCheck {
+    fn name(&self) -> &'static str {
+        "beacon_node_reorgs"
+    }
+
+    async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+        if self
+            .events
+            .as_ref()
+            .is_some_and(|events| events.is_connected())
+        {
+            // Heads from before a gap in polling would be mistaken for a reorg.
+            self.heads.lock().unwrap().clear();
+        } else {
+            self.detect().await?;
+        }
+
+        let deepest = self.reorgs.deepest_recent();
+        let outcome = match (&deepest, self.max_depth) {
+            (Some((reorg, ago)), Some(max_depth)) if reorg.depth > max_depth => {
+                CheckOutcome::fail(format!(
+                    "{} reorged {} slots deep at slot {}, {}s ago, more than {max_depth}",
+                    self.beacon_node.kind(),
+                    reorg.depth,
+                    reorg.slot,
+                    ago.as_secs()
+                ))
+            }
+            _ => CheckOutcome::pass(),
+        };
+        Ok(outcome.with_observed(json!({
+            "deepest_recent_reorg": deepest.map(|(reorg, ago)| json!({
+                "slot": reorg.slot,
+                "depth": reorg.depth,
+                "old_head_block": reorg.old_head_block,
+                "new_head_block": reorg.new_head_block,
+                "secs_ago": ago.as_secs(),
+            })),
+        })))
+    }
+}
+
+struct HeadProgress {
+    head_slot: u64,
+    since: Instant,
+}
+
+/// A node that keeps reporting the same head slot is stuck, even if it claims to be synced. The
+/// head is timed against the slot clock, so how often we poll does not matter.
+pub struct BeaconNodeHeadProgressCheck {
+    beacon_node: Arc<BeaconNode>,
+    max_stall_slots: u64,
+    last_progress: Mutex<Option<HeadProgress>>,
+}
+
+impl BeaconNodeHeadProgressCheck {
+    pub fn new(beacon_node: Arc<BeaconNode>, max_stall_slots: u64) -> Self {
+        Self {
+            beacon_node,
+            max_stall_slots,
+            last_progress: Mutex::new(None),
+        }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for BeaconNodeHeadProgressCheck {
+    fn name(&self) -> &'static str {
+        "beacon_node_head_progress"
+    }
+
+    async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+        let head_slot = self.beacon_node.sync_status_shared().await?.head_slot();
+
+        let mut last_progress = self.last_progress.lock().unwrap();
+        let progress = match last_progress.as_mut() {
+            Some(progress) if progress.head_slot == head_slot => progress,
+            _ => last_progress.insert(HeadProgress {
+                head_slot,
+                since: Instant::now(),
+            }),
+        };
+
+        let stuck_secs = progress.since.elapsed().as_secs();
+        let stalled_slots = stuck_secs / SECONDS_PER_SLOT;
+        let outcome = if stalled_slots > self.max_stall_slots {
+            CheckOutcome::fail(format!(
+                "{} head slot {head_slot} has not advanced in {stalled_slots} slots ({stuck_secs}s), more than {}",
+                self.beacon_node.kind(),
+                self.max_stall_slots
+            ))
+        } else {
+            CheckOutcome::pass()
+        };
+        Ok(outcome.with_observed(json!({
+            "head_slot": head_slot,
+            "unchanged_secs": stuck_secs,
+            "stalled_slots": stalled_slots,
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        events::{BeaconEvent, FinalizedCheckpointEvent, HeadEvent},
+        health_check::CheckStatus,
+        node_client::ClientSettings,
+    };
+
+    use super::*;
+
+    async fn mock_syncing(server: &mut mockito::Server, data: serde_json::Value) -> mockito::Mock {
+        server
+            .mock("GET", "/eth/v1/node/syncing")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({ "data": data }).to_string())
+            .create_async()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_peer_count_check() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/eth/v1/node/peer_count")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({ "data": { "connected": "7" } }).to_string())
+            .create_async()
+            .await;
+
+        let beacon_node = Arc::new(BeaconNode::new(server.url()));
+
+        let check = BeaconNodePeerCountCheck::new(beacon_node.clone(), 10);
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Fail);
+
+        let check = BeaconNodePeerCountCheck::new(beacon_node, 5);
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Pass);
+    }
+
+    async fn mock_head_slot(server: &mut mockito::Server, slot: u64) -> mockito::Mock {
+        server
+            .mock("GET", "/eth/v1/beacon/headers/head")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "data": {
+                        "root": "0xabc",
+                        "header": {
+                            "message": { "slot": slot.to_string(), "parent_root": "0xabb" }
+                        }
+                    }
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_genesis_check() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/eth/v1/beacon/genesis")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "data": {
+                        "genesis_time": "1695902400",
+                        "genesis_validators_root": Network::Holesky.genesis_validators_root(),
+                        "genesis_fork_version": "0x01017000"
+                    }
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        let beacon_node = Arc::new(BeaconNode::new(server.url()));
+
+        let check = BeaconNodeGenesisCheck::new(beacon_node.clone(), Network::Holesky);
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Pass);
+
+        let check = BeaconNodeGenesisCheck::new(beacon_node, Network::Mainnet);
+        let outcome = check.evaluate().await.unwrap();
+        assert_eq!(outcome.status, CheckStatus::Fail);
+        assert!(outcome.reason.unwrap().contains("expected 0x4b363db9"));
+    }
+
+    #[tokio::test]
+    async fn test_head_freshness_check() {
+        let mut server = mockito::Server::new_async().await;
+        let beacon_node = Arc::new(BeaconNode::new(server.url()));
+        let check = BeaconNodeHeadFreshnessCheck::new(beacon_node, Network::Mainnet, 5);
+
+        let fresh_mock =
+            mock_head_slot(&mut server, clock::current_slot(&Network::Mainnet) - 1).await;
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Pass);
+
+        fresh_mock.remove_async().await;
+        mock_head_slot(&mut server, clock::current_slot(&Network::Mainnet) - 10).await;
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Fail);
+    }
+
+    async fn mock_finalized_epoch(server: &mut mockito::Server, epoch: u64) -> mockito::Mock {
+        let checkpoint = |epoch: u64| json!({ "epoch": epoch.to_string(), "root": "0xabc" });
+        server
+            .mock("GET", "/eth/v1/beacon/states/head/finality_checkpoints")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "data": {
+                        "previous_justified": checkpoint(epoch),
+                        "current_justified": checkpoint(epoch + 1),
+                        "finalized": checkpoint(epoch)
+                    }
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_finality_check() {
+        let mut server = mockito::Server::new_async().await;
+        let beacon_node = Arc::new(BeaconNode::new(server.url()));
+        let check = BeaconNodeFinalityCheck::new(beacon_node, Network::Mainnet, 4);
+
+        let finalizing_mock =
+            mock_finalized_epoch(&mut server, clock::current_epoch(&Network::Mainnet) - 2).await;
+        let outcome = check.evaluate().await.unwrap();
+        assert_eq!(outcome.status, CheckStatus::Pass);
+        assert_eq!(outcome.observed.unwrap()["finality_distance"], 2);
+
+        finalizing_mock.remove_async().await;
+        mock_finalized_epoch(&mut server, clock::current_epoch(&Network::Mainnet) - 10).await;
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Fail);
+    }
+
+    #[tokio::test]
+    async fn test_finality_check_with_events() {
+        let mut server = mockito::Server::new_async().await;
+        let beacon_node = Arc::new(BeaconNode::new(server.url()));
+        let events = Arc::new(BeaconEvents::new(
+            beacon_node.clone(),
+            &ClientSettings::default(),
+            Duration::from_secs(60),
+        ));
+        let check = BeaconNodeFinalityCheck::new(beacon_node, Network::Mainnet, 4)
+            .with_events(events.clone());
+        let current_epoch = clock::current_epoch(&Network::Mainnet);
+        let finalized = |epoch: u64| {
+            BeaconEvent::FinalizedCheckpoint(FinalizedCheckpointEvent {
+                epoch,
+                block: "0xcd".to_string(),
+            })
+        };
+        mock_finalized_epoch(&mut server, current_epoch - 10).await;
+
+        // Connected, but without a checkpoint event since, so the checkpoint is polled.
+        events.set_connected(true);
+        let outcome = check.evaluate().await.unwrap();
+        assert_eq!(outcome.status, CheckStatus::Fail);
+        assert_eq!(outcome.observed.unwrap()["source"], "poll");
+
+        events.handle(finalized(current_epoch - 2));
+        let outcome = check.evaluate().await.unwrap();
+        assert_eq!(outcome.status, CheckStatus::Pass);
+        assert_eq!(outcome.observed.unwrap()["source"], "event");
+
+        // A checkpoint that would fail is confirmed by polling.
+        events.handle(finalized(current_epoch - 10));
+        let outcome = check.evaluate().await.unwrap();
+        assert_eq!(outcome.status, CheckStatus::Fail);
+        assert_eq!(outcome.observed.unwrap()["source"], "poll");
+    }
+
+    #[tokio::test]
+    async fn test_head_events_check() {
+        let server = mockito::Server::new_async().await;
+        let events = Arc::new(BeaconEvents::new(
+            Arc::new(BeaconNode::new(server.url())),
+            &ClientSettings::default(),
+            Duration::from_secs(60),
+        ));
+
+        let check = BeaconNodeHeadEventsCheck::new(events, 4);
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Warn);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_head_events_check_fails_on_a_quiet_stream() {
+        let events = Arc::new(BeaconEvents::new(
+            Arc::new(BeaconNode::new(String::new())),
+            &ClientSettings::default(),
+            Duration::from_secs(60),
+        ));
+        let check = BeaconNodeHeadEventsCheck::new(events.clone(), 4);
+
+        events.set_connected(true);
+        events.handle(BeaconEvent::Head(HeadEvent {
+            slot: 10,
+            block: "0xab".to_string(),
+        }));
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Pass);
+
+        // Still connected, but no head for more than 4 slots.
+        tokio::time::advance(Duration::from_secs(5 * SECONDS_PER_SLOT)).await;
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Fail);
+
+        // Reconnecting doesn't make up for the missing heads.
+        events.set_connected(false);
+        events.set_connected(true);
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Fail);
+    }
+
+    async fn mock_head(
+        server: &mut mockito::Server,
+        path: &str,
+        slot: u64,
+        root: &str,
+        parent_root: &str,
+    ) -> mockito::Mock {
+        server
+            .mock("GET", path)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "data": {
+                        "root": root,
+                        "header": {
+                            "message": { "slot": slot.to_string(), "parent_root": parent_root }
+                        }
+                    }
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_reorg_check() {
+        // Without a depth to fail on the walk goes back as far as we remember, with one it stops
+        // once the reorg is deeper.
+        for (max_depth, looks_up_ancestor, status, tracked_heads) in [
+            (None, true, CheckStatus::Pass, 2),
+            (Some(1), false, CheckStatus::Fail, 1),
+        ] {
+            let mut server = mockito::Server::new_async().await;
+            let beacon_node = Arc::new(BeaconNode::new(server.url()));
+            let reorgs = Arc::new(ReorgTracker::new(Duration::from_secs(60)));
+            let check = BeaconNodeReorgCheck::new(beacon_node, reorgs.clone(), max_depth);
+
+            let head = "/eth/v1/beacon/headers/head";
+            for (slot, root, parent_root) in
+                [(10, "0xa", "0x9"), (11, "0xb", "0xa"), (12, "0xc", "0xb")]
+            {
+                let mock = mock_head(&mut server, head, slot, root, parent_root).await;
+                assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Pass);
+                mock.remove_async().await;
+            }
+            assert_eq!(reorgs.deepest_recent(), None);
+
+            // 0xb and 0xc were orphaned, 0xa is still canonical.
+            mock_head(&mut server, head, 13, "0xe", "0xd").await;
+            for slot in [11, 12] {
+                server
+                    .mock("GET", format!("/eth/v1/beacon/headers/{slot}").as_str())
+                    .with_status(404)
+                    .create_async()
+                    .await;
+            }
+            // Without a mock a lookup would error.
+            let ancestor_mock = if looks_up_ancestor {
+                Some(mock_head(&mut server, "/eth/v1/beacon/headers/10", 10, "0xa", "0x9").await)
+            } else {
+                None
+            };
+
+            let outcome = check.evaluate().await.unwrap();
+            assert_eq!(outcome.status, status);
+            let (reorg, _) = reorgs.deepest_recent().unwrap();
+            assert_eq!(reorg.depth, 2);
+            assert_eq!(reorg.old_head_block, "0xc");
+            assert_eq!(reorg.new_head_block, "0xe");
+            if let Some(ancestor_mock) = ancestor_mock {
+                ancestor_mock.assert_async().await;
+            }
+
+            // The orphaned heads are forgotten, the new head is tracked.
+            assert_eq!(check.heads.lock().unwrap().len(), tracked_heads);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_head_progress_check() {
+        let mut server = mockito::Server::new_async().await;
+        let beacon_node = Arc::new(BeaconNode::new(server.url()));
+        let check = BeaconNodeHeadProgressCheck::new(beacon_node, 2);
+
+        let syncing = |head_slot: &str| {
+            json!({
+                "el_offline": false,
+                "head_slot": head_slot,
+                "is_optimistic": false,
+                "is_syncing": false,
+                "sync_distance": "0"
+            })
+        };
+
+        let stall_for = |slots: u64| {
+            let mut last_progress = check.last_progress.lock().unwrap();
+            let progress = last_progress.as_mut().unwrap();
+            progress.since -= Duration::from_secs(slots * SECONDS_PER_SLOT);
+        };
+
+        let mock = mock_syncing(&mut server, syncing("100")).await;
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Pass);
+        // Polling often does not make the head look stuck, only time does.
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Pass);
+        stall_for(2);
+        // Exactly the allowed stall still passes.
+        let outcome = check.evaluate().await.unwrap();
+        assert_eq!(outcome.status, CheckStatus::Pass);
+        assert_eq!(outcome.observed.unwrap()["stalled_slots"], 2);
+        stall_for(1);
+        let outcome = check.evaluate().await.unwrap();
+        assert_eq!(outcome.status, CheckStatus::Fail);
+        assert_eq!(outcome.observed.unwrap()["stalled_slots"], 3);
+
+        mock.remove_async().await;
+        mock_syncing(&mut server, syncing("101")).await;
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Pass);
+    }
+
+    #[tokio::test]
+    async fn test_sync_status_checks() {
+        let mut server = mockito::Server::new_async().await;
+        mock_syncing(
+            &mut server,
+            json!({
+                "el_offline": true,
+                "head_slot": "5478944",
+                "is_optimistic": false,
+                "is_syncing": false,
+                "sync_distance": "2"
+            }),
+        )
+        .await;
+
+        let beacon_node = Arc::new(BeaconNode::new(server.url()));
+
+        let check = BeaconNodeSyncingCheck::new(beacon_node.clone());
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Pass);
+
+        let check = BeaconNodeOptimisticCheck::new(beacon_node.clone());
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Pass);
+
+        let check = BeaconNodeElOfflineCheck::new(beacon_node.clone());
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Fail);
+
+        let check = BeaconNodeSyncDistanceCheck::new(beacon_node.clone(), 1);
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Fail);
+
+        let check = BeaconNodeSyncDistanceCheck::new(beacon_node, 2);
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Pass);
+    }
+}
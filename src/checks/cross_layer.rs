@@ -0,0 +1,173 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tracing::debug;
+
+use crate::{
+    beacon_node::BeaconNode,
+    execution_node::ExecutionNode,
+    health_check::{CheckOutcome, HealthCheck},
+    metrics,
+};
+
+/// Each node can look healthy on its own while the beacon node is actually driving a different,
+/// or lagging, execution node. The execution node should know the block the beacon head commits
+/// to.
+pub struct CrossLayerHeadCheck {
+    beacon_node: Arc<BeaconNode>,
+    execution_node: Arc<ExecutionNode>,
+    max_block_distance: u64,
+}
+
+impl CrossLayerHeadCheck {
+    pub fn new(
+        beacon_node: Arc<BeaconNode>,
+        execution_node: Arc<ExecutionNode>,
+        max_block_distance: u64,
+    ) -> Self {
+        Self {
+            beacon_node,
+            execution_node,
+            max_block_distance,
+        }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for CrossLayerHeadCheck {
+    fn name(&self) -> &'static str {
+        "cross_layer_head"
+    }
+
+    async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+        let head_block = self.beacon_node.head_block().await?;
+        let Some(payload) = head_block.execution_payload() else {
+            debug!(
+                "{} head block at slot {} has no execution payload",
+                self.beacon_node.kind(),
+                head_block.slot()
+            );
+            return Ok(CheckOutcome::pass());
+        };
+
+        if self
+            .execution_node
+            .block_by_hash(&payload.block_hash)
+            .await?
+            .is_some()
+        {
+            metrics::CROSS_LAYER_BLOCK_DISTANCE
+                .with_label_values(&[&self.beacon_node.pair])
+                .set(0);
+            return Ok(CheckOutcome::pass().with_observed(json!({
+                "beacon_slot": head_block.slot(),
+                "block_hash": payload.block_hash,
+                "block_number": payload.block_number,
+                "known": true,
+            })));
+        }
+
+        // Unknown to the execution node, a block or two apart is the two layers racing each
+        // other, more means they follow different chains.
+        let execution_head = self.execution_node.latest_block().await?.number;
+        let distance = execution_head.abs_diff(payload.block_number);
+        metrics::CROSS_LAYER_BLOCK_DISTANCE
+            .with_label_values(&[&self.beacon_node.pair])
+            .set(distance as i64);
+
+        let reason = format!(
+            "{} does not know block {} ({}) of the {} head, its head is block {execution_head}, {distance} blocks apart",
+            self.execution_node.kind(),
+            payload.block_number,
+            payload.block_hash,
+            self.beacon_node.kind(),
+        );
+        let outcome = if distance > self.max_block_distance {
+            CheckOutcome::fail(format!("{reason}, more than {}", self.max_block_distance))
+        } else {
+            CheckOutcome::warn(reason)
+        };
+        Ok(outcome.with_observed(json!({
+            "beacon_slot": head_block.slot(),
+            "block_hash": payload.block_hash,
+            "block_number": payload.block_number,
+            "known": false,
+            "execution_head": execution_head,
+            "distance": distance,
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockito::Matcher;
+    use serde_json::{json, Value};
+
+    use crate::health_check::CheckStatus;
+
+    use super::*;
+
+    async fn mock_beacon_head(server: &mut mockito::Server, block_number: u64) -> mockito::Mock {
+        server
+            .mock("GET", "/eth/v1/beacon/blinded_blocks/head")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                json!({
+                    "data": {
+                        "message": {
+                            "slot": "8000000",
+                            "body": {
+                                "execution_payload_header": {
+                                    "block_hash": "0xabc",
+                                    "block_number": block_number.to_string()
+                                }
+                            }
+                        }
+                    }
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await
+    }
+
+    async fn mock_rpc(server: &mut mockito::Server, method: &str, result: Value) -> mockito::Mock {
+        server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJson(json!({ "method": method })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({ "jsonrpc": "2.0", "id": 1, "result": result }).to_string())
+            .create_async()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_cross_layer_head_check() {
+        let mut beacon_server = mockito::Server::new_async().await;
+        let mut execution_server = mockito::Server::new_async().await;
+        let check = CrossLayerHeadCheck::new(
+            Arc::new(BeaconNode::new(beacon_server.url())),
+            Arc::new(ExecutionNode::new(execution_server.url())),
+            2,
+        );
+        let block = |number: u64| json!({ "number": format!("{number:#x}"), "timestamp": "0x0" });
+
+        mock_beacon_head(&mut beacon_server, 100).await;
+        let known_mock = mock_rpc(&mut execution_server, "eth_getBlockByHash", block(100)).await;
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Pass);
+
+        known_mock.remove_async().await;
+        mock_rpc(&mut execution_server, "eth_getBlockByHash", Value::Null).await;
+        let head_mock = mock_rpc(&mut execution_server, "eth_getBlockByNumber", block(99)).await;
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Warn);
+
+        head_mock.remove_async().await;
+        mock_rpc(&mut execution_server, "eth_getBlockByNumber", block(90)).await;
+        let outcome = check.evaluate().await.unwrap();
+        assert_eq!(outcome.status, CheckStatus::Fail);
+        assert_eq!(outcome.observed.unwrap()["distance"], 10);
+    }
+}
@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::{
+    engine_api::EngineApi,
+    health_check::{CheckOutcome, HealthCheck},
+    node_client::NodeClientError,
+};
+
+/// The beacon node can't do anything without the Engine API, this checks it is reachable and
+/// accepts the shared JWT secret.
+pub struct EngineApiCheck {
+    engine_api: Arc<EngineApi>,
+}
+
+impl EngineApiCheck {
+    pub fn new(engine_api: Arc<EngineApi>) -> Self {
+        Self { engine_api }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for EngineApiCheck {
+    fn name(&self) -> &'static str {
+        "engine_api"
+    }
+
+    async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+        // Errors go to the registry, which labels them by kind like for every other check.
+        match self.engine_api.exchange_capabilities().await {
+            Ok(capabilities) => Ok(CheckOutcome::pass().with_observed(capabilities)),
+            Err(e @ NodeClientError::Unauthorized(_)) => {
+                Err(anyhow::Error::new(e)
+                    .context("engine api rejected our jwt, check the jwt secret"))
+            }
+            Err(e) => Err(anyhow::Error::new(e).context("engine api")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        engine_api::parse_jwt_secret,
+        health_check::{CheckRegistry, CheckStatus},
+        metrics,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_engine_api_check_reports_auth_failure() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/")
+            .with_status(401)
+            .create_async()
+            .await;
+
+        let secret = parse_jwt_secret(&"ab".repeat(32)).unwrap();
+        let check = EngineApiCheck::new(Arc::new(EngineApi::new(server.url(), secret)));
+        let mut registry = CheckRegistry::new(vec![]).with_pair("engine-api-auth-test");
+        registry.register(check);
+        let errors = metrics::CHECK_ERRORS.with_label_values(&[
+            "engine-api-auth-test",
+            "engine_api",
+            "unauthorized",
+        ]);
+        let errors_before = errors.get();
+
+        let results = registry.run().await;
+
+        assert_eq!(results[0].outcome.status, CheckStatus::Fail);
+        assert_eq!(results[0].error_kind, Some("unauthorized"));
+        assert_eq!(results[0].consecutive_errors, 1);
+        assert!(results[0]
+            .outcome
+            .reason
+            .as_deref()
+            .unwrap()
+            .contains("check the jwt secret"));
+        assert_eq!(errors.get(), errors_before + 1);
+    }
+}
@@ -0,0 +1,356 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tracing::debug;
+
+use crate::{
+    clock::{self, SECONDS_PER_SLOT},
+    env::Network,
+    execution_node::{ExecutionNode, ExecutionSyncStatus},
+    health_check::{CheckOutcome, HealthCheck},
+    metrics,
+};
+
+pub struct ExecutionNodeSyncingCheck {
+    execution_node: Arc<ExecutionNode>,
+}
+
+impl ExecutionNodeSyncingCheck {
+    pub fn new(execution_node: Arc<ExecutionNode>) -> Self {
+        Self { execution_node }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for ExecutionNodeSyncingCheck {
+    fn name(&self) -> &'static str {
+        "execution_node_syncing"
+    }
+
+    async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+        let outcome = match self.execution_node.syncing().await? {
+            ExecutionSyncStatus::Synced => {
+                metrics::EXECUTION_NODE_SYNCING
+                    .with_label_values(&[&self.execution_node.pair])
+                    .set(0);
+                metrics::EXECUTION_NODE_SYNC_BLOCKS_REMAINING
+                    .with_label_values(&[&self.execution_node.pair])
+                    .set(0);
+                CheckOutcome::pass().with_observed(json!({ "syncing": false }))
+            }
+            ExecutionSyncStatus::Syncing(progress) => {
+                metrics::EXECUTION_NODE_SYNCING
+                    .with_label_values(&[&self.execution_node.pair])
+                    .set(1);
+                metrics::EXECUTION_NODE_SYNC_BLOCKS_REMAINING
+                    .with_label_values(&[&self.execution_node.pair])
+                    .set(progress.blocks_remaining() as i64);
+                let mut reason = format!(
+                    "{} is syncing, at block {} of {}, {} blocks remaining ({:.2}%)",
+                    self.execution_node.kind(),
+                    progress.current_block,
+                    progress.highest_block,
+                    progress.blocks_remaining(),
+                    progress.percentage()
+                );
+                // Erigon only moves current block once the last stage is done, the stage tells
+                // us where it actually is.
+                if let Some(stage) = progress.lagging_stage() {
+                    reason.push_str(&format!(
+                        ", stage {} at block {}",
+                        stage.stage_name, stage.block_number
+                    ));
+                }
+                CheckOutcome::fail(reason).with_observed(json!({
+                    "syncing": true,
+                    "current_block": progress.current_block,
+                    "highest_block": progress.highest_block,
+                    "blocks_remaining": progress.blocks_remaining(),
+                    "percentage": progress.percentage(),
+                    "lagging_stage": progress.lagging_stage(),
+                }))
+            }
+        };
+        Ok(outcome)
+    }
+}
+
+/// Counts peers with `net_peerCount`, which every supported client answers alike.
+pub struct ExecutionNodePeerCountCheck {
+    execution_node: Arc<ExecutionNode>,
+    min_peer_count: u64,
+}
+
+impl ExecutionNodePeerCountCheck {
+    pub fn new(execution_node: Arc<ExecutionNode>, min_peer_count: u64) -> Self {
+        Self {
+            execution_node,
+            min_peer_count,
+        }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for ExecutionNodePeerCountCheck {
+    fn name(&self) -> &'static str {
+        "execution_node_peer_count"
+    }
+
+    async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+        let peer_count = self.execution_node.peer_count().await?;
+        metrics::EXECUTION_NODE_PEER_COUNT
+            .with_label_values(&[&self.execution_node.pair])
+            .set(peer_count as i64);
+        let outcome = if peer_count < self.min_peer_count {
+            CheckOutcome::fail(format!(
+                "{} has {peer_count} peers, less than {}",
+                self.execution_node.kind(),
+                self.min_peer_count
+            ))
+        } else {
+            CheckOutcome::pass()
+        };
+        Ok(outcome.with_observed(peer_count))
+    }
+}
+
+/// Compares `eth_chainId` with the chain id of the configured `NETWORK`, e.g. 1 on mainnet. Syncing
+/// and peers look the same on any chain, the id tells them apart.
+pub struct ExecutionNodeChainIdCheck {
+    execution_node: Arc<ExecutionNode>,
+    network: Network,
+}
+
+impl ExecutionNodeChainIdCheck {
+    pub fn new(execution_node: Arc<ExecutionNode>, network: Network) -> Self {
+        Self {
+            execution_node,
+            network,
+        }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for ExecutionNodeChainIdCheck {
+    fn name(&self) -> &'static str {
+        "execution_node_chain_id"
+    }
+
+    async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+        let chain_id = self.execution_node.chain_id().await?;
+        let expected = self.network.chain_id();
+        let outcome = if chain_id != expected {
+            CheckOutcome::fail(format!(
+                "{} is on chain {chain_id}, expected {expected} for {}, check NETWORK and EXECUTION_NODE_URL",
+                self.execution_node.kind(),
+                self.network
+            ))
+        } else {
+            CheckOutcome::pass()
+        };
+        Ok(outcome.with_observed(chain_id))
+    }
+}
+
+/// An execution node can claim to be synced and have plenty of peers while its head stalled.
+pub struct ExecutionNodeHeadFreshnessCheck {
+    execution_node: Arc<ExecutionNode>,
+    max_lag_slots: u64,
+}
+
+impl ExecutionNodeHeadFreshnessCheck {
+    pub fn new(execution_node: Arc<ExecutionNode>, max_lag_slots: u64) -> Self {
+        Self {
+            execution_node,
+            max_lag_slots,
+        }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for ExecutionNodeHeadFreshnessCheck {
+    fn name(&self) -> &'static str {
+        "execution_node_head_freshness"
+    }
+
+    async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+        let block = self.execution_node.latest_block().await?;
+
+        let age_secs = clock::unix_now().saturating_sub(block.timestamp);
+        metrics::EXECUTION_NODE_HEAD_BLOCK_NUMBER
+            .with_label_values(&[&self.execution_node.pair])
+            .set(block.number as i64);
+        metrics::EXECUTION_NODE_HEAD_AGE_SECONDS
+            .with_label_values(&[&self.execution_node.pair])
+            .set(age_secs as i64);
+
+        let max_age_secs = self.max_lag_slots * SECONDS_PER_SLOT;
+        let outcome = if age_secs > max_age_secs {
+            CheckOutcome::fail(format!(
+                "{} head block {} is {age_secs}s old, more than {max_age_secs}s",
+                self.execution_node.kind(),
+                block.number
+            ))
+        } else {
+            CheckOutcome::pass()
+        };
+        Ok(outcome.with_observed(json!({
+            "block_number": block.number,
+            "age_secs": age_secs,
+        })))
+    }
+}
+
+/// Nethermind judges its own health, including sync state and peers. Only registered when the
+/// execution node runs Nethermind.
+pub struct NethermindHealthCheck {
+    execution_node: Arc<ExecutionNode>,
+}
+
+impl NethermindHealthCheck {
+    pub fn new(execution_node: Arc<ExecutionNode>) -> Self {
+        Self { execution_node }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for NethermindHealthCheck {
+    fn name(&self) -> &'static str {
+        "nethermind_health"
+    }
+
+    async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+        let Some(health) = self.execution_node.nethermind_health().await? else {
+            debug!("nethermind health checks are not enabled, skipping");
+            return Ok(CheckOutcome::pass());
+        };
+        let outcome = match health.status.as_str() {
+            "Healthy" => CheckOutcome::pass(),
+            "Degraded" => CheckOutcome::warn("nethermind reports it is degraded"),
+            status => CheckOutcome::fail(format!("nethermind reports it is {status}")),
+        };
+        Ok(outcome.with_observed(health.status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::health_check::CheckStatus;
+
+    use super::*;
+
+    async fn mock_result(server: &mut mockito::Server, result: &str) -> mockito::Mock {
+        server
+            .mock("POST", "/")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(format!(r#"{{"jsonrpc":"2.0","id":1,"result":{result}}}"#))
+            .create_async()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_chain_id_check() {
+        let mut server = mockito::Server::new_async().await;
+        mock_result(&mut server, r#""0x4268""#).await;
+        let execution_node = Arc::new(ExecutionNode::new(server.url()));
+
+        let check = ExecutionNodeChainIdCheck::new(execution_node.clone(), Network::Holesky);
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Pass);
+
+        let check = ExecutionNodeChainIdCheck::new(execution_node, Network::Mainnet);
+        let outcome = check.evaluate().await.unwrap();
+        assert_eq!(outcome.status, CheckStatus::Fail);
+        assert!(outcome
+            .reason
+            .unwrap()
+            .contains("on chain 17000, expected 1"));
+    }
+
+    #[tokio::test]
+    async fn test_syncing_check_fails_when_syncing() {
+        let mut server = mockito::Server::new_async().await;
+        mock_result(
+            &mut server,
+            r#"{"startingBlock":"0x0","currentBlock":"0x10","highestBlock":"0x20"}"#,
+        )
+        .await;
+
+        let check = ExecutionNodeSyncingCheck::new(Arc::new(ExecutionNode::new(server.url())));
+        let outcome = check.evaluate().await.unwrap();
+
+        assert_eq!(outcome.status, CheckStatus::Fail);
+        assert!(outcome.reason.unwrap().contains("16 blocks remaining"));
+    }
+
+    #[tokio::test]
+    async fn test_peer_count_check() {
+        let mut server = mockito::Server::new_async().await;
+        mock_result(&mut server, r#""0x3""#).await;
+
+        let execution_node = Arc::new(ExecutionNode::new(server.url()));
+
+        let check = ExecutionNodePeerCountCheck::new(execution_node.clone(), 5);
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Fail);
+
+        let check = ExecutionNodePeerCountCheck::new(execution_node, 2);
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Pass);
+    }
+
+    #[tokio::test]
+    async fn test_head_freshness_check() {
+        let mut server = mockito::Server::new_async().await;
+        let fresh_timestamp = clock::unix_now() - SECONDS_PER_SLOT;
+        let fresh_mock = mock_result(
+            &mut server,
+            &format!(r#"{{"number":"0x10","timestamp":"{fresh_timestamp:#x}"}}"#),
+        )
+        .await;
+
+        let check =
+            ExecutionNodeHeadFreshnessCheck::new(Arc::new(ExecutionNode::new(server.url())), 5);
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Pass);
+
+        fresh_mock.remove_async().await;
+        let stale_timestamp = clock::unix_now() - 10 * SECONDS_PER_SLOT;
+        mock_result(
+            &mut server,
+            &format!(r#"{{"number":"0x10","timestamp":"{stale_timestamp:#x}"}}"#),
+        )
+        .await;
+
+        assert_eq!(check.evaluate().await.unwrap().status, CheckStatus::Fail);
+    }
+
+    #[tokio::test]
+    async fn test_nethermind_health_check() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/health")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"status":"Degraded"}"#)
+            .create_async()
+            .await;
+
+        let check = NethermindHealthCheck::new(Arc::new(ExecutionNode::new(server.url())));
+        let outcome = check.evaluate().await.unwrap();
+
+        assert_eq!(outcome.status, CheckStatus::Warn);
+    }
+
+    #[tokio::test]
+    async fn test_peer_count_check_errors_on_bad_response() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/")
+            .with_status(500)
+            .create_async()
+            .await;
+
+        let check = ExecutionNodePeerCountCheck::new(Arc::new(ExecutionNode::new(server.url())), 2);
+
+        assert!(check.evaluate().await.is_err());
+    }
+}
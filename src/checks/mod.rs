@@ -0,0 +1,7 @@
+//! The concrete health checks, grouped by the node they look at. Checks comparing the two nodes
+//! of a pair live in [`cross_layer`].
+
+pub mod beacon_node;
+pub mod cross_layer;
+pub mod engine_api;
+pub mod execution_node;
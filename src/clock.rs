@@ -0,0 +1,57 @@
+//! Wall clock helpers, used to judge how fresh what the nodes report is.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::env::Network;
+
+pub const SECONDS_PER_SLOT: u64 = 12;
+
+pub const SLOTS_PER_EPOCH: u64 = 32;
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// The slot the wall clock is in at `unix_time`, 0 before genesis.
+pub fn slot_at(genesis_time: u64, unix_time: u64) -> u64 {
+    unix_time.saturating_sub(genesis_time) / SECONDS_PER_SLOT
+}
+
+pub fn current_slot(network: &Network) -> u64 {
+    slot_at(network.genesis_time(), unix_now())
+}
+
+pub fn epoch_of(slot: u64) -> u64 {
+    slot / SLOTS_PER_EPOCH
+}
+
+pub fn current_epoch(network: &Network) -> u64 {
+    epoch_of(current_slot(network))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slot_at() {
+        let genesis_time = Network::Mainnet.genesis_time();
+        assert_eq!(slot_at(genesis_time, genesis_time - 1), 0);
+        assert_eq!(slot_at(genesis_time, genesis_time), 0);
+        assert_eq!(slot_at(genesis_time, genesis_time + 11), 0);
+        assert_eq!(slot_at(genesis_time, genesis_time + 12), 1);
+        // Slot 8,000,000 on mainnet.
+        assert_eq!(slot_at(genesis_time, 1_702_824_023), 8_000_000);
+    }
+
+    #[test]
+    fn test_epoch_of() {
+        assert_eq!(epoch_of(0), 0);
+        assert_eq!(epoch_of(31), 0);
+        assert_eq!(epoch_of(32), 1);
+        assert_eq!(epoch_of(8_000_000), 250_000);
+    }
+}
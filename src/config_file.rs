@@ -0,0 +1,174 @@
+//! Optional TOML or YAML config file, pointed at by `CONFIG_PATH`. Keys are the lower cased names
+//! of the environment variables, which take precedence over the file.
+
+use std::path::Path;
+
+use anyhow::Context;
+use serde::Deserialize;
+
+/// The variables of a node pair, see [`crate::env::NodePairConfig`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NodePairFile {
+    pub name: String,
+    pub beacon_client: Option<String>,
+    pub beacon_url: Option<String>,
+    pub engine_api_url: Option<String>,
+    pub execution_node_url: Option<String>,
+    pub jwt_secret_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub beacon_client: Option<String>,
+    pub beacon_url: Option<String>,
+    pub bind_public_interface: Option<bool>,
+    pub check_timeout_secs: Option<u64>,
+    pub connect_timeout_secs: Option<u64>,
+    pub disabled_checks: Option<Vec<String>>,
+    pub engine_api_url: Option<String>,
+    pub execution_node_url: Option<String>,
+    pub jwt_secret_path: Option<String>,
+    pub max_beacon_head_lag_slots: Option<u64>,
+    pub max_consecutive_check_errors: Option<u64>,
+    pub max_cross_layer_block_distance: Option<u64>,
+    pub max_evaluation_age_secs: Option<u64>,
+    pub max_execution_head_lag_slots: Option<u64>,
+    pub max_finality_distance_epochs: Option<u64>,
+    pub max_head_stall_slots: Option<u64>,
+    pub max_heartbeat_age_secs: Option<u64>,
+    pub max_reorg_depth: Option<u64>,
+    pub max_slots_without_head_event: Option<u64>,
+    pub max_startup_time_secs: Option<u64>,
+    pub max_sync_distance: Option<u64>,
+    pub min_beacon_peers: Option<u64>,
+    pub min_execution_node_peers: Option<u64>,
+    pub min_readiness_dwell_secs: Option<u64>,
+    pub network: Option<String>,
+    #[serde(default)]
+    pub node_pairs: Vec<NodePairFile>,
+    pub not_ready_after_failures: Option<u64>,
+    pub poll_interval_secs: Option<u64>,
+    pub ready_after_successes: Option<u64>,
+    pub reorg_window_slots: Option<u64>,
+    pub request_timeout_secs: Option<u64>,
+    pub shutdown_drain_secs: Option<u64>,
+    pub subscribe_events: Option<bool>,
+}
+
+impl ConfigFile {
+    /// Reads a `.toml`, `.yaml` or `.yml` file. Unknown keys and wrong types are errors, which
+    /// point at the offending line.
+    pub fn read(path: &str) -> anyhow::Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file {path}"))?;
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default();
+        Self::parse(&raw, extension).with_context(|| format!("invalid config file {path}"))
+    }
+
+    fn parse(raw: &str, extension: &str) -> anyhow::Result<Self> {
+        let config = match extension {
+            "toml" => toml::from_str(raw)?,
+            "yaml" | "yml" => serde_yaml::from_str(raw)?,
+            extension => {
+                anyhow::bail!("unsupported extension {extension:?}, expected toml, yaml or yml")
+            }
+        };
+        Ok(config)
+    }
+
+    /// The default pair is configured at the top level, other pairs in `node_pairs`.
+    pub fn node_pair(&self, name: &str, default_pair: &str) -> Option<NodePairFile> {
+        let pair = self
+            .node_pairs
+            .iter()
+            .find(|pair| pair.name == name)
+            .cloned();
+        if pair.is_some() || name != default_pair {
+            return pair;
+        }
+        Some(NodePairFile {
+            name: name.to_string(),
+            beacon_client: self.beacon_client.clone(),
+            beacon_url: self.beacon_url.clone(),
+            engine_api_url: self.engine_api_url.clone(),
+            execution_node_url: self.execution_node_url.clone(),
+            jwt_secret_path: self.jwt_secret_path.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_toml() {
+        let config = ConfigFile::parse(
+            r#"
+network = "holesky"
+poll_interval_secs = 6
+
+[[node_pairs]]
+name = "alpha"
+beacon_url = "http://alpha:5052"
+execution_node_url = "http://alpha:8545"
+"#,
+            "toml",
+        )
+        .unwrap();
+
+        assert_eq!(config.network.as_deref(), Some("holesky"));
+        assert_eq!(config.poll_interval_secs, Some(6));
+        assert_eq!(
+            config.node_pair("alpha", "default").unwrap().beacon_url,
+            Some("http://alpha:5052".to_string())
+        );
+        assert_eq!(config.node_pair("beta", "default"), None);
+    }
+
+    #[test]
+    fn test_parse_yaml() {
+        let config = ConfigFile::parse(
+            "beacon_url: http://localhost:5052\ndisabled_checks:\n  - engine_api\n",
+            "yaml",
+        )
+        .unwrap();
+
+        assert_eq!(config.disabled_checks, Some(vec!["engine_api".to_string()]));
+        assert_eq!(
+            config.node_pair("default", "default").unwrap().beacon_url,
+            Some("http://localhost:5052".to_string())
+        );
+    }
+
+    #[test]
+    fn test_errors_point_at_line() {
+        let error = ConfigFile::parse("network = \"mainnet\"\npoll_intervl_secs = 4\n", "toml")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("line 2"), "{error}");
+
+        let error = ConfigFile::parse("network: mainnet\nmin_beacon_peers: many\n", "yaml")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("line 2"), "{error}");
+
+        let error = ConfigFile::parse(
+            "[[node_pairs]]\nbeacon_url = \"http://alpha:5052\"\n",
+            "toml",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(
+            error.contains("line 1") && error.contains("name"),
+            "{error}"
+        );
+
+        assert!(ConfigFile::parse("", "json").is_err());
+    }
+}
@@ -0,0 +1,212 @@
+//! Client for the authenticated Engine API the beacon node uses to drive the execution node.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+use crate::{
+    clock,
+    env::DEFAULT_PAIR,
+    metrics,
+    node_client::{ClientSettings, NodeClientError},
+};
+
+/// Capabilities we claim to support, the execution node answers with the ones it supports.
+const CAPABILITIES: [&str; 6] = [
+    "engine_exchangeCapabilities",
+    "engine_forkchoiceUpdatedV3",
+    "engine_getPayloadV3",
+    "engine_newPayloadV3",
+    "engine_getPayloadV4",
+    "engine_newPayloadV4",
+];
+
+/// Parses the hex encoded 32 byte secret shared by the beacon and execution node.
+pub fn parse_jwt_secret(raw: &str) -> anyhow::Result<Vec<u8>> {
+    let secret = hex::decode(raw.trim().trim_start_matches("0x"))?;
+    if secret.len() != 32 {
+        anyhow::bail!("jwt secret should be 32 bytes, got {}", secret.len());
+    }
+    Ok(secret)
+}
+
+pub struct EngineApi {
+    pub url: String,
+    /// Name of the node pair this Engine API belongs to, labels its metrics.
+    pub pair: String,
+    client: Client,
+    jwt_secret: Vec<u8>,
+}
+
+impl EngineApi {
+    pub fn new(url: String, jwt_secret: Vec<u8>) -> Self {
+        Self::with_settings(url, jwt_secret, &ClientSettings::default())
+    }
+
+    pub fn with_settings(url: String, jwt_secret: Vec<u8>, settings: &ClientSettings) -> Self {
+        Self {
+            url,
+            pair: DEFAULT_PAIR.to_string(),
+            client: settings.build(),
+            jwt_secret,
+        }
+    }
+
+    pub fn with_pair(mut self, pair: impl Into<String>) -> Self {
+        self.pair = pair.into();
+        self
+    }
+
+    pub fn from_secret_file(
+        url: String,
+        jwt_secret_path: &str,
+        settings: &ClientSettings,
+    ) -> anyhow::Result<Self> {
+        let raw = std::fs::read_to_string(jwt_secret_path)
+            .map_err(|e| anyhow::anyhow!("failed to read jwt secret {jwt_secret_path}: {e}"))?;
+        let jwt_secret = parse_jwt_secret(&raw)?;
+        Ok(Self::with_settings(url, jwt_secret, settings))
+    }
+
+    /// An HS256 JWT with only an `iat` claim, which is all the Engine API requires.
+    fn token(&self, issued_at: u64) -> String {
+        let header = URL_SAFE_NO_PAD.encode(json!({ "alg": "HS256", "typ": "JWT" }).to_string());
+        let claims = URL_SAFE_NO_PAD.encode(json!({ "iat": issued_at }).to_string());
+        let signing_input = format!("{header}.{claims}");
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.jwt_secret)
+            .expect("hmac accepts keys of any length");
+        mac.update(signing_input.as_bytes());
+        let signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+
+        format!("{signing_input}.{signature}")
+    }
+
+    pub async fn exchange_capabilities(&self) -> Result<Vec<String>, NodeClientError> {
+        let result = self.exchange_capabilities_inner().await;
+        if let Err(e) = &result {
+            metrics::REQUEST_ERRORS
+                .with_label_values(&[
+                    &self.pair,
+                    "engine_api",
+                    "engine_exchangeCapabilities",
+                    e.kind(),
+                ])
+                .inc();
+        }
+        result
+    }
+
+    async fn exchange_capabilities_inner(&self) -> Result<Vec<String>, NodeClientError> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "method": "engine_exchangeCapabilities",
+            "params": [CAPABILITIES],
+            "id": 1
+        });
+        let res = self
+            .client
+            .post(&self.url)
+            .bearer_auth(self.token(clock::unix_now()))
+            .json(&body)
+            .send()
+            .await?;
+        // 401 and 403 mean the execution node rejected our JWT, usually a mismatched secret.
+        let res = NodeClientError::check_status(res).await?;
+        let body: Value = res.json().await?;
+
+        if let Some(error) = NodeClientError::from_json_rpc_body(&body) {
+            return Err(error);
+        }
+
+        body["result"]
+            .as_array()
+            .ok_or_else(|| NodeClientError::malformed(format!("result is not an array: {body}")))?
+            .iter()
+            .map(|capability| {
+                capability
+                    .as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| NodeClientError::malformed("capability is not string"))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "0x6a5e7b1f3c9d2e8a4b0c7f6d5e3a1b9c8d7e6f5a4b3c2d1e0f9a8b7c6d5e4f3a";
+
+    #[test]
+    fn test_parse_jwt_secret() {
+        assert_eq!(parse_jwt_secret(SECRET).unwrap().len(), 32);
+        assert_eq!(
+            parse_jwt_secret(&format!("{}\n", &SECRET[2..]))
+                .unwrap()
+                .len(),
+            32
+        );
+        assert!(parse_jwt_secret("0x1234").is_err());
+        assert!(parse_jwt_secret("not hex").is_err());
+    }
+
+    #[test]
+    fn test_token() {
+        let engine_api = EngineApi::new(
+            "http://localhost:8551".to_string(),
+            parse_jwt_secret(SECRET).unwrap(),
+        );
+        let token = engine_api.token(1_700_000_000);
+
+        let parts: Vec<&str> = token.split('.').collect();
+        assert_eq!(parts.len(), 3);
+        let claims = URL_SAFE_NO_PAD.decode(parts[1]).unwrap();
+        assert_eq!(claims, br#"{"iat":1700000000}"#);
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(&parse_jwt_secret(SECRET).unwrap()).unwrap();
+        mac.update(format!("{}.{}", parts[0], parts[1]).as_bytes());
+        mac.verify_slice(&URL_SAFE_NO_PAD.decode(parts[2]).unwrap())
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_exchange_capabilities() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/")
+            .match_header("authorization", mockito::Matcher::Regex("^Bearer ".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"jsonrpc":"2.0","id":1,"result":["engine_newPayloadV3","engine_forkchoiceUpdatedV3"]}"#,
+            )
+            .create_async()
+            .await;
+
+        let engine_api = EngineApi::new(server.url(), parse_jwt_secret(SECRET).unwrap());
+        let capabilities = engine_api.exchange_capabilities().await.unwrap();
+
+        assert_eq!(capabilities.len(), 2);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_exchange_capabilities_unauthorized() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/")
+            .with_status(401)
+            .with_body("invalid token")
+            .create_async()
+            .await;
+
+        let engine_api = EngineApi::new(server.url(), parse_jwt_secret(SECRET).unwrap());
+        let error = engine_api.exchange_capabilities().await.unwrap_err();
+
+        assert!(matches!(error, NodeClientError::Unauthorized(_)));
+    }
+}
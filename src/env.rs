@@ -1,16 +1,32 @@
 //! Fns to read variables from the environment more conveniently and help other functions figure
 //! out what environment they're running in.
 
-use std::{env, fmt, sync::LazyLock};
+use std::{env, fmt, sync::LazyLock, time::Duration};
 
 use tracing::{debug, warn};
 
-const SECRET_LOG_BLACKLIST: [&str; 0] = [];
+use crate::{
+    beacon_node::BeaconClientKind, clock::SLOTS_PER_EPOCH, config_file::ConfigFile,
+    node_client::ClientSettings, readiness::Hysteresis,
+};
+
+/// Name of the node pair configured through the unprefixed variables, e.g. `BEACON_URL`.
+pub const DEFAULT_PAIR: &str = "default";
+
+/// Node urls regularly embed an API key, e.g. for hosted nodes.
+const SECRET_LOG_BLACKLIST: [&str; 3] = ["BEACON_URL", "ENGINE_API_URL", "EXECUTION_NODE_URL"];
 
 pub static ENV_CONFIG: LazyLock<EnvConfig> = LazyLock::new(get_env_config);
 
+/// Also matches the prefixed variables of node pairs, e.g. `ALPHA_BEACON_URL`.
 fn obfuscate_if_secret(blacklist: &[&str], key: &str, value: &str) -> String {
-    if blacklist.contains(&key) {
+    let is_secret = blacklist.iter().any(|secret| {
+        key == *secret
+            || key
+                .strip_suffix(secret)
+                .is_some_and(|prefix| prefix.ends_with('_'))
+    });
+    if is_secret {
         let mut last_four = value.to_string();
         last_four.drain(0..value.len().saturating_sub(4));
         format!("****{last_four}")
@@ -54,6 +70,17 @@ pub fn get_env_bool(key: &str) -> Option<bool> {
     })
 }
 
+pub fn get_env_u64(key: &str) -> Option<u64> {
+    get_env_var(key).map(|var| {
+        var.parse::<u64>()
+            .unwrap_or_else(|_| panic!("invalid u64 value {var} for {key}"))
+    })
+}
+
+pub fn get_env_duration_secs(key: &str) -> Option<Duration> {
+    get_env_u64(key).map(Duration::from_secs)
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Network {
     Mainnet,
@@ -74,10 +101,13 @@ impl fmt::Display for Network {
 }
 
 pub fn get_network() -> Network {
-    let network_str = get_env_var("NETWORK");
+    parse_network(get_env_var("NETWORK"))
+}
+
+fn parse_network(network_str: Option<String>) -> Network {
     match network_str {
         None => {
-            warn!("no NETWORK in env, assuming Mainnet");
+            warn!("no NETWORK configured, assuming Mainnet");
             Network::Mainnet
         }
         Some(str) => match str.to_lowercase().as_ref() {
@@ -90,27 +120,594 @@ pub fn get_network() -> Network {
     }
 }
 
+impl Network {
+    /// Unix timestamp of the beacon chain genesis.
+    pub fn genesis_time(&self) -> u64 {
+        match self {
+            Network::Mainnet => 1606824023,
+            Network::Goerli => 1616508000,
+            Network::Holesky => 1695902400,
+            Network::Hoodi => 1742213400,
+        }
+    }
+
+    /// What `eth_chainId` returns on the network.
+    pub fn chain_id(&self) -> u64 {
+        match self {
+            Network::Mainnet => 1,
+            Network::Goerli => 5,
+            Network::Holesky => 17000,
+            Network::Hoodi => 560048,
+        }
+    }
+
+    /// Identifies the beacon chain, unlike the genesis time it can't collide between networks.
+    pub fn genesis_validators_root(&self) -> &'static str {
+        match self {
+            Network::Mainnet => {
+                "0x4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95"
+            }
+            Network::Goerli => "0x043db0d9a83813551ee2f33450d23797757d430911a9320530ad8a0eabc43efb",
+            Network::Holesky => {
+                "0x9143aa7c615a7f7115e2b6aac319c03529df8242ae705fba9df39b79c59fa8b1"
+            }
+            Network::Hoodi => "0x212f13fc4df078b6cb7db228f1c8307566dcecf900867401a92023d7ba99cb5f",
+        }
+    }
+
+    pub fn default_min_execution_node_peers(&self) -> u64 {
+        match self {
+            Network::Mainnet => 5,
+            _ => 2,
+        }
+    }
+}
+
+/// `auto` or unset means detect the client from the version the node reports.
+pub fn get_beacon_client(key: &str) -> Option<BeaconClientKind> {
+    parse_beacon_client(key, get_env_var(key)?)
+}
+
+fn parse_beacon_client(key: &str, str: String) -> Option<BeaconClientKind> {
+    if str.eq_ignore_ascii_case("auto") {
+        return None;
+    }
+    match BeaconClientKind::parse(&str) {
+        Some(kind) => Some(kind),
+        None => panic!("{key} present: {str}, but not one of [auto, grandine, lighthouse, lodestar, nimbus, prysm, teku], panicking!"),
+    }
+}
+
+/// A beacon node and the execution node it drives, monitored and reported on together.
 #[derive(Debug, Clone)]
-pub struct EnvConfig {
+pub struct NodePairConfig {
+    pub name: String,
+    /// None means detect it.
+    pub beacon_client: Option<BeaconClientKind>,
     pub beacon_url: String,
-    pub bind_public_interface: bool,
+    /// The authenticated Engine API of the execution node, checked when set together with
+    /// `jwt_secret_path`.
+    pub engine_api_url: Option<String>,
     pub execution_node_url: String,
+    /// Path to the hex encoded secret shared by the beacon and execution node.
+    pub jwt_secret_path: Option<String>,
+}
+
+/// Prefix of the variables of a pair, e.g. `ALPHA_` for pair `alpha`. The default pair uses the
+/// unprefixed variables.
+fn node_pair_prefix(name: &str) -> String {
+    if name == DEFAULT_PAIR {
+        String::new()
+    } else {
+        format!("{}_", name.to_uppercase().replace('-', "_"))
+    }
+}
+
+/// Variables of a pair are prefixed with its name, e.g. `ALPHA_BEACON_URL` for pair `alpha`, and
+/// take precedence over the pair in the config file.
+fn get_node_pair(name: &str, file: &ConfigFile) -> NodePairConfig {
+    let prefix = node_pair_prefix(name);
+    let key = |key: &str| format!("{prefix}{key}");
+    let file_pair = file.node_pair(name, DEFAULT_PAIR).unwrap_or_default();
+    let required = |key: String, file_value: Option<String>| {
+        get_env_var(&key)
+            .or(file_value)
+            .unwrap_or_else(|| panic!("{key} should be in env or the config file"))
+    };
+
+    NodePairConfig {
+        name: name.to_string(),
+        beacon_client: get_env_var(&key("BEACON_CLIENT"))
+            .or(file_pair.beacon_client)
+            .and_then(|str| parse_beacon_client(&key("BEACON_CLIENT"), str)),
+        beacon_url: required(key("BEACON_URL"), file_pair.beacon_url),
+        engine_api_url: get_env_var(&key("ENGINE_API_URL")).or(file_pair.engine_api_url),
+        execution_node_url: required(key("EXECUTION_NODE_URL"), file_pair.execution_node_url),
+        jwt_secret_path: get_env_var(&key("JWT_SECRET_PATH")).or(file_pair.jwt_secret_path),
+    }
+}
+
+/// `NODE_PAIRS` lists the names of the pairs to monitor, when unset the pairs of the config file
+/// are monitored, and without those a single default pair.
+fn get_node_pairs(file: &ConfigFile) -> Vec<NodePairConfig> {
+    match get_env_var("NODE_PAIRS") {
+        None if file.node_pairs.is_empty() => vec![get_node_pair(DEFAULT_PAIR, file)],
+        None => file
+            .node_pairs
+            .iter()
+            .map(|pair| get_node_pair(&pair.name, file))
+            .collect(),
+        Some(names) => names
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| get_node_pair(name, file))
+            .collect(),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EnvConfig {
+    pub bind_public_interface: bool,
+    /// How long all checks together may take each tick, checks still running by then fail.
+    pub check_timeout: Duration,
+    pub connect_timeout: Duration,
+    /// Names of health checks that should not be registered.
+    pub disabled_checks: Vec<String>,
+    /// How many slots the beacon node's head may lag the wall clock slot.
+    pub max_beacon_head_lag_slots: u64,
+    /// Give up on the node pair once a check errored this many times in a row, None means never.
+    pub max_consecutive_check_errors: Option<u64>,
+    /// How many blocks the beacon head's execution block may be apart from the execution node's
+    /// head while the execution node doesn't know it yet.
+    pub max_cross_layer_block_distance: u64,
+    /// Readiness turns 503 when the checks were last evaluated longer ago than this, e.g. because
+    /// a node hangs.
+    pub max_evaluation_age: Duration,
+    /// How many slots the execution node's head block may lag the wall clock.
+    pub max_execution_head_lag_slots: u64,
+    /// How many epochs the finalized checkpoint may lag the wall clock epoch. Without trouble it
+    /// is two epochs behind.
+    pub max_finality_distance_epochs: u64,
+    /// How many slots the beacon head slot may stay the same. A missed proposal keeps it put for a
+    /// slot now and then.
+    pub max_head_stall_slots: u64,
+    /// `/livez` turns 503 when the monitor loop made no progress for longer than this.
+    pub max_heartbeat_age: Duration,
+    /// Not ready for `reorg_window_slots` after a reorg deeper than this many slots, None means
+    /// reorgs are only logged and counted.
+    pub max_reorg_depth: Option<u64>,
+    /// With `subscribe_events`, how many slots may pass without a head event. The stream is only
+    /// reconnected `IDLE_GRACE_SLOTS` later.
+    pub max_slots_without_head_event: u64,
+    /// How long to wait for both nodes to respond before giving up.
+    pub max_startup_time: Duration,
+    /// We allow to be one slot behind by default, this naturally happens all the time.
+    pub max_sync_distance: u64,
+    pub min_beacon_peers: u64,
+    pub min_execution_node_peers: u64,
+    /// Minimum time between two readiness transitions.
+    pub min_readiness_dwell: Duration,
     pub network: Network,
+    pub node_pairs: Vec<NodePairConfig>,
+    /// Consecutive failing polls before a ready node turns not ready.
+    pub not_ready_after_failures: u64,
+    pub poll_interval: Duration,
+    /// Consecutive passing polls before a not ready node turns ready.
+    pub ready_after_successes: u64,
+    /// How long a reorg deeper than `max_reorg_depth` keeps us not ready.
+    pub reorg_window_slots: u64,
+    pub request_timeout: Duration,
+    /// How long to keep serving, reporting not ready, after SIGTERM before stopping.
+    pub shutdown_drain: Duration,
+    /// Consume the beacon node's event stream on top of polling.
+    pub subscribe_events: bool,
+}
+
+impl EnvConfig {
+    /// Checks the config makes sense, we'd rather refuse to start than silently never be ready.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.node_pairs.is_empty() {
+            anyhow::bail!("NODE_PAIRS must name at least one node pair");
+        }
+
+        for (i, pair) in self.node_pairs.iter().enumerate() {
+            if pair.name.is_empty() {
+                anyhow::bail!("node pair names must not be empty");
+            }
+
+            // Names end up in urls and metric labels.
+            if !pair
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+            {
+                anyhow::bail!(
+                    "node pair name {} may only contain lowercase letters, digits, - and _",
+                    pair.name
+                );
+            }
+
+            if self.node_pairs[..i]
+                .iter()
+                .any(|other| other.name == pair.name)
+            {
+                anyhow::bail!("node pair {} is listed more than once", pair.name);
+            }
+
+            if pair.engine_api_url.is_some() != pair.jwt_secret_path.is_some() {
+                anyhow::bail!(
+                    "ENGINE_API_URL and JWT_SECRET_PATH must be set together, node pair {}",
+                    pair.name
+                );
+            }
+        }
+
+        if self.poll_interval.is_zero() {
+            anyhow::bail!("POLL_INTERVAL_SECS must be greater than 0");
+        }
+
+        if self.connect_timeout.is_zero() || self.request_timeout.is_zero() {
+            anyhow::bail!("CONNECT_TIMEOUT_SECS and REQUEST_TIMEOUT_SECS must be greater than 0");
+        }
+
+        if self.check_timeout.is_zero() {
+            anyhow::bail!("CHECK_TIMEOUT_SECS must be greater than 0");
+        }
+
+        if self.check_timeout < self.request_timeout {
+            warn!("CHECK_TIMEOUT_SECS is less than REQUEST_TIMEOUT_SECS, slow requests fail the check before they time out");
+        }
+
+        if self.max_evaluation_age < self.poll_interval + self.check_timeout {
+            anyhow::bail!(
+                "MAX_EVALUATION_AGE_SECS ({}s) must be at least POLL_INTERVAL_SECS plus CHECK_TIMEOUT_SECS ({}s)",
+                self.max_evaluation_age.as_secs(),
+                (self.poll_interval + self.check_timeout).as_secs()
+            );
+        }
+
+        if self.max_heartbeat_age < self.max_evaluation_age {
+            anyhow::bail!(
+                "MAX_HEARTBEAT_AGE_SECS ({}s) must be at least MAX_EVALUATION_AGE_SECS ({}s)",
+                self.max_heartbeat_age.as_secs(),
+                self.max_evaluation_age.as_secs()
+            );
+        }
+
+        if self.max_startup_time < self.poll_interval {
+            anyhow::bail!(
+                "MAX_STARTUP_TIME_SECS ({}s) must be at least POLL_INTERVAL_SECS ({}s)",
+                self.max_startup_time.as_secs(),
+                self.poll_interval.as_secs()
+            );
+        }
+
+        if self.max_beacon_head_lag_slots == 0 {
+            anyhow::bail!("MAX_BEACON_HEAD_LAG_SLOTS must be greater than 0");
+        }
+
+        if self.max_execution_head_lag_slots == 0 {
+            anyhow::bail!("MAX_EXECUTION_HEAD_LAG_SLOTS must be greater than 0");
+        }
+
+        if self.max_finality_distance_epochs < 2 {
+            anyhow::bail!(
+                "MAX_FINALITY_DISTANCE_EPOCHS must be at least 2, a healthy chain finalizes two epochs behind"
+            );
+        }
+
+        if self.max_consecutive_check_errors == Some(0) {
+            anyhow::bail!(
+                "MAX_CONSECUTIVE_CHECK_ERRORS must be greater than 0, unset it to never exit"
+            );
+        }
+
+        if self.max_reorg_depth == Some(0) {
+            anyhow::bail!(
+                "MAX_REORG_DEPTH must be greater than 0, unset it to never fail on reorgs"
+            );
+        }
+
+        if self.reorg_window_slots == 0 {
+            anyhow::bail!("REORG_WINDOW_SLOTS must be greater than 0");
+        }
+
+        if self.max_slots_without_head_event == 0 {
+            anyhow::bail!("MAX_SLOTS_WITHOUT_HEAD_EVENT must be greater than 0");
+        }
+
+        if self.max_head_stall_slots == 0 {
+            anyhow::bail!("MAX_HEAD_STALL_SLOTS must be greater than 0");
+        }
+
+        if self.not_ready_after_failures == 0 || self.ready_after_successes == 0 {
+            anyhow::bail!(
+                "NOT_READY_AFTER_FAILURES and READY_AFTER_SUCCESSES must be greater than 0"
+            );
+        }
+
+        if self.min_beacon_peers == 0 {
+            warn!("MIN_BEACON_PEERS is 0, beacon peer count check always passes");
+        }
+
+        if self.min_execution_node_peers == 0 {
+            warn!("MIN_EXECUTION_NODE_PEERS is 0, execution node peer count check always passes");
+        }
+
+        Ok(())
+    }
+
+    pub fn hysteresis(&self) -> Hysteresis {
+        Hysteresis {
+            failures_before_not_ready: self.not_ready_after_failures,
+            successes_before_ready: self.ready_after_successes,
+            min_dwell: self.min_readiness_dwell,
+        }
+    }
+
+    pub fn client_settings(&self) -> ClientSettings {
+        ClientSettings {
+            connect_timeout: self.connect_timeout,
+            request_timeout: self.request_timeout,
+        }
+    }
+
+    /// The effective config as environment variables, one `KEY=value` per line, with secrets
+    /// obfuscated. Unset optional variables are left out.
+    pub fn print(&self) -> String {
+        let secs = |duration: Duration| duration.as_secs().to_string();
+        let mut vars = vec![
+            (
+                "BIND_PUBLIC_INTERFACE".to_string(),
+                self.bind_public_interface.to_string(),
+            ),
+            ("CHECK_TIMEOUT_SECS".to_string(), secs(self.check_timeout)),
+            (
+                "CONNECT_TIMEOUT_SECS".to_string(),
+                secs(self.connect_timeout),
+            ),
+            (
+                "DISABLED_CHECKS".to_string(),
+                self.disabled_checks.join(","),
+            ),
+            (
+                "MAX_BEACON_HEAD_LAG_SLOTS".to_string(),
+                self.max_beacon_head_lag_slots.to_string(),
+            ),
+        ];
+        if let Some(max_consecutive_check_errors) = self.max_consecutive_check_errors {
+            vars.push((
+                "MAX_CONSECUTIVE_CHECK_ERRORS".to_string(),
+                max_consecutive_check_errors.to_string(),
+            ));
+        }
+        vars.extend([
+            (
+                "MAX_CROSS_LAYER_BLOCK_DISTANCE".to_string(),
+                self.max_cross_layer_block_distance.to_string(),
+            ),
+            (
+                "MAX_EVALUATION_AGE_SECS".to_string(),
+                secs(self.max_evaluation_age),
+            ),
+            (
+                "MAX_EXECUTION_HEAD_LAG_SLOTS".to_string(),
+                self.max_execution_head_lag_slots.to_string(),
+            ),
+            (
+                "MAX_FINALITY_DISTANCE_EPOCHS".to_string(),
+                self.max_finality_distance_epochs.to_string(),
+            ),
+            (
+                "MAX_HEAD_STALL_SLOTS".to_string(),
+                self.max_head_stall_slots.to_string(),
+            ),
+            (
+                "MAX_HEARTBEAT_AGE_SECS".to_string(),
+                secs(self.max_heartbeat_age),
+            ),
+        ]);
+        if let Some(max_reorg_depth) = self.max_reorg_depth {
+            vars.push(("MAX_REORG_DEPTH".to_string(), max_reorg_depth.to_string()));
+        }
+        vars.extend([
+            (
+                "MAX_SLOTS_WITHOUT_HEAD_EVENT".to_string(),
+                self.max_slots_without_head_event.to_string(),
+            ),
+            (
+                "MAX_STARTUP_TIME_SECS".to_string(),
+                secs(self.max_startup_time),
+            ),
+            (
+                "MAX_SYNC_DISTANCE".to_string(),
+                self.max_sync_distance.to_string(),
+            ),
+            (
+                "MIN_BEACON_PEERS".to_string(),
+                self.min_beacon_peers.to_string(),
+            ),
+            (
+                "MIN_EXECUTION_NODE_PEERS".to_string(),
+                self.min_execution_node_peers.to_string(),
+            ),
+            (
+                "MIN_READINESS_DWELL_SECS".to_string(),
+                secs(self.min_readiness_dwell),
+            ),
+            ("NETWORK".to_string(), self.network.to_string()),
+            (
+                "NODE_PAIRS".to_string(),
+                self.node_pairs
+                    .iter()
+                    .map(|pair| pair.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            (
+                "NOT_READY_AFTER_FAILURES".to_string(),
+                self.not_ready_after_failures.to_string(),
+            ),
+            ("POLL_INTERVAL_SECS".to_string(), secs(self.poll_interval)),
+            (
+                "READY_AFTER_SUCCESSES".to_string(),
+                self.ready_after_successes.to_string(),
+            ),
+            (
+                "REORG_WINDOW_SLOTS".to_string(),
+                self.reorg_window_slots.to_string(),
+            ),
+            (
+                "REQUEST_TIMEOUT_SECS".to_string(),
+                secs(self.request_timeout),
+            ),
+            ("SHUTDOWN_DRAIN_SECS".to_string(), secs(self.shutdown_drain)),
+            (
+                "SUBSCRIBE_EVENTS".to_string(),
+                self.subscribe_events.to_string(),
+            ),
+        ]);
+
+        for pair in &self.node_pairs {
+            let prefix = node_pair_prefix(&pair.name);
+            let pair_vars = [
+                (
+                    "BEACON_CLIENT",
+                    Some(
+                        pair.beacon_client
+                            .map_or_else(|| "auto".to_string(), |kind| kind.to_string()),
+                    ),
+                ),
+                ("BEACON_URL", Some(pair.beacon_url.clone())),
+                ("ENGINE_API_URL", pair.engine_api_url.clone()),
+                ("EXECUTION_NODE_URL", Some(pair.execution_node_url.clone())),
+                ("JWT_SECRET_PATH", pair.jwt_secret_path.clone()),
+            ];
+            for (key, value) in pair_vars {
+                if let Some(value) = value {
+                    vars.push((format!("{prefix}{key}"), value));
+                }
+            }
+        }
+
+        vars.iter()
+            .map(|(key, value)| {
+                let value = obfuscate_if_secret(&SECRET_LOG_BLACKLIST, key, value);
+                format!("{key}={value}\n")
+            })
+            .collect()
+    }
 }
 
 fn get_env_config() -> EnvConfig {
     dotenvy::dotenv().ok();
 
+    let file = get_env_var("CONFIG_PATH")
+        .map(|path| ConfigFile::read(&path).unwrap_or_else(|e| panic!("{e:#}")))
+        .unwrap_or_default();
+
+    env_config_from(&file)
+}
+
+/// Environment variables take precedence over the config file, which takes precedence over the
+/// defaults.
+fn env_config_from(file: &ConfigFile) -> EnvConfig {
+    let secs = |secs: Option<u64>| secs.map(Duration::from_secs);
+
+    let network = parse_network(get_env_var("NETWORK").or(file.network.clone()));
+
     EnvConfig {
-        beacon_url: get_env_var("BEACON_URL").expect("BEACON_URL not set"),
-        bind_public_interface: get_env_bool("BIND_PUBLIC_INTERFACE").unwrap_or(true),
-        execution_node_url: get_env_var("EXECUTION_NODE_URL").expect("EXECUTION_NODE_URL not set"),
-        network: get_network(),
+        bind_public_interface: get_env_bool("BIND_PUBLIC_INTERFACE")
+            .or(file.bind_public_interface)
+            .unwrap_or(true),
+        check_timeout: get_env_duration_secs("CHECK_TIMEOUT_SECS")
+            .or(secs(file.check_timeout_secs))
+            .unwrap_or(Duration::from_secs(15)),
+        connect_timeout: get_env_duration_secs("CONNECT_TIMEOUT_SECS")
+            .or(secs(file.connect_timeout_secs))
+            .unwrap_or(Duration::from_secs(5)),
+        disabled_checks: get_env_var("DISABLED_CHECKS")
+            .map(|var| {
+                var.split(',')
+                    .map(|name| name.trim().to_string())
+                    .filter(|name| !name.is_empty())
+                    .collect()
+            })
+            .or(file.disabled_checks.clone())
+            .unwrap_or_default(),
+        max_beacon_head_lag_slots: get_env_u64("MAX_BEACON_HEAD_LAG_SLOTS")
+            .or(file.max_beacon_head_lag_slots)
+            .unwrap_or(5),
+        max_consecutive_check_errors: get_env_u64("MAX_CONSECUTIVE_CHECK_ERRORS")
+            .or(file.max_consecutive_check_errors),
+        max_cross_layer_block_distance: get_env_u64("MAX_CROSS_LAYER_BLOCK_DISTANCE")
+            .or(file.max_cross_layer_block_distance)
+            .unwrap_or(2),
+        max_evaluation_age: get_env_duration_secs("MAX_EVALUATION_AGE_SECS")
+            .or(secs(file.max_evaluation_age_secs))
+            .unwrap_or(Duration::from_secs(60)),
+        max_execution_head_lag_slots: get_env_u64("MAX_EXECUTION_HEAD_LAG_SLOTS")
+            .or(file.max_execution_head_lag_slots)
+            .unwrap_or(5),
+        max_finality_distance_epochs: get_env_u64("MAX_FINALITY_DISTANCE_EPOCHS")
+            .or(file.max_finality_distance_epochs)
+            .unwrap_or(4),
+        max_head_stall_slots: get_env_u64("MAX_HEAD_STALL_SLOTS")
+            .or(file.max_head_stall_slots)
+            .unwrap_or(3),
+        max_heartbeat_age: get_env_duration_secs("MAX_HEARTBEAT_AGE_SECS")
+            .or(secs(file.max_heartbeat_age_secs))
+            .unwrap_or(Duration::from_secs(120)),
+        max_reorg_depth: get_env_u64("MAX_REORG_DEPTH").or(file.max_reorg_depth),
+        max_slots_without_head_event: get_env_u64("MAX_SLOTS_WITHOUT_HEAD_EVENT")
+            .or(file.max_slots_without_head_event)
+            .unwrap_or(4),
+        max_startup_time: get_env_duration_secs("MAX_STARTUP_TIME_SECS")
+            .or(secs(file.max_startup_time_secs))
+            .unwrap_or(Duration::from_secs(60 * 15)),
+        max_sync_distance: get_env_u64("MAX_SYNC_DISTANCE")
+            .or(file.max_sync_distance)
+            .unwrap_or(1),
+        min_beacon_peers: get_env_u64("MIN_BEACON_PEERS")
+            .or(file.min_beacon_peers)
+            .unwrap_or(10),
+        min_execution_node_peers: get_env_u64("MIN_EXECUTION_NODE_PEERS")
+            .or(file.min_execution_node_peers)
+            .unwrap_or_else(|| network.default_min_execution_node_peers()),
+        min_readiness_dwell: get_env_duration_secs("MIN_READINESS_DWELL_SECS")
+            .or(secs(file.min_readiness_dwell_secs))
+            .unwrap_or(Duration::ZERO),
+        node_pairs: get_node_pairs(file),
+        not_ready_after_failures: get_env_u64("NOT_READY_AFTER_FAILURES")
+            .or(file.not_ready_after_failures)
+            .unwrap_or(1),
+        poll_interval: get_env_duration_secs("POLL_INTERVAL_SECS")
+            .or(secs(file.poll_interval_secs))
+            .unwrap_or(Duration::from_secs(4)),
+        ready_after_successes: get_env_u64("READY_AFTER_SUCCESSES")
+            .or(file.ready_after_successes)
+            .unwrap_or(1),
+        reorg_window_slots: get_env_u64("REORG_WINDOW_SLOTS")
+            .or(file.reorg_window_slots)
+            .unwrap_or(SLOTS_PER_EPOCH),
+        request_timeout: get_env_duration_secs("REQUEST_TIMEOUT_SECS")
+            .or(secs(file.request_timeout_secs))
+            .unwrap_or(Duration::from_secs(10)),
+        shutdown_drain: get_env_duration_secs("SHUTDOWN_DRAIN_SECS")
+            .or(secs(file.shutdown_drain_secs))
+            .unwrap_or(Duration::from_secs(5)),
+        subscribe_events: get_env_bool("SUBSCRIBE_EVENTS")
+            .or(file.subscribe_events)
+            .unwrap_or(false),
+        network,
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::config_file::NodePairFile;
+
     use super::*;
 
     #[test]
@@ -171,6 +768,134 @@ mod tests {
         assert_eq!(get_env_bool(test_key), Some(false));
     }
 
+    #[test]
+    fn test_get_env_u64() {
+        let test_key = "TEST_KEY_U64";
+        std::env::set_var(test_key, "42");
+        assert_eq!(get_env_u64(test_key), Some(42));
+        assert_eq!(get_env_u64("DOESNT_EXIST"), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_get_env_u64_panics_on_invalid() {
+        let test_key = "TEST_KEY_U64_INVALID";
+        std::env::set_var(test_key, "four");
+        get_env_u64(test_key);
+    }
+
+    fn test_pair(name: &str) -> NodePairConfig {
+        NodePairConfig {
+            name: name.to_string(),
+            beacon_client: None,
+            beacon_url: "http://localhost:5052".to_string(),
+            engine_api_url: None,
+            execution_node_url: "http://localhost:8545".to_string(),
+            jwt_secret_path: None,
+        }
+    }
+
+    fn test_config() -> EnvConfig {
+        EnvConfig {
+            bind_public_interface: false,
+            check_timeout: Duration::from_secs(15),
+            connect_timeout: Duration::from_secs(5),
+            disabled_checks: vec![],
+            max_beacon_head_lag_slots: 5,
+            max_consecutive_check_errors: None,
+            max_cross_layer_block_distance: 2,
+            max_evaluation_age: Duration::from_secs(60),
+            max_execution_head_lag_slots: 5,
+            max_finality_distance_epochs: 4,
+            max_head_stall_slots: 3,
+            max_heartbeat_age: Duration::from_secs(120),
+            max_reorg_depth: None,
+            max_slots_without_head_event: 4,
+            max_startup_time: Duration::from_secs(60 * 15),
+            max_sync_distance: 1,
+            min_beacon_peers: 10,
+            min_execution_node_peers: 5,
+            min_readiness_dwell: Duration::ZERO,
+            network: Network::Mainnet,
+            node_pairs: vec![test_pair(DEFAULT_PAIR)],
+            not_ready_after_failures: 1,
+            poll_interval: Duration::from_secs(4),
+            ready_after_successes: 1,
+            reorg_window_slots: 32,
+            request_timeout: Duration::from_secs(10),
+            shutdown_drain: Duration::from_secs(5),
+            subscribe_events: false,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(test_config().validate().is_ok());
+
+        let config = EnvConfig {
+            poll_interval: Duration::ZERO,
+            ..test_config()
+        };
+        assert!(config.validate().is_err());
+
+        let config = EnvConfig {
+            max_startup_time: Duration::from_secs(1),
+            ..test_config()
+        };
+        assert!(config.validate().is_err());
+
+        let config = EnvConfig {
+            max_finality_distance_epochs: 1,
+            ..test_config()
+        };
+        assert!(config.validate().is_err());
+
+        let config = EnvConfig {
+            max_evaluation_age: Duration::from_secs(15),
+            ..test_config()
+        };
+        assert!(config.validate().is_err());
+
+        let config = EnvConfig {
+            node_pairs: vec![NodePairConfig {
+                engine_api_url: Some("http://localhost:8551".to_string()),
+                ..test_pair(DEFAULT_PAIR)
+            }],
+            ..test_config()
+        };
+        assert!(config.validate().is_err());
+
+        let config = EnvConfig {
+            node_pairs: vec![test_pair("alpha"), test_pair("beta")],
+            ..test_config()
+        };
+        assert!(config.validate().is_ok());
+
+        let config = EnvConfig {
+            node_pairs: vec![test_pair("alpha"), test_pair("alpha")],
+            ..test_config()
+        };
+        assert!(config.validate().is_err());
+
+        let config = EnvConfig {
+            node_pairs: vec![test_pair("Alpha/1")],
+            ..test_config()
+        };
+        assert!(config.validate().is_err());
+
+        let config = EnvConfig {
+            node_pairs: vec![test_pair("")],
+            ..test_config()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_default_min_execution_node_peers() {
+        assert_eq!(Network::Mainnet.default_min_execution_node_peers(), 5);
+        assert_eq!(Network::Holesky.default_min_execution_node_peers(), 2);
+    }
+
     #[test]
     fn test_obfuscate_if_secret() {
         let secret_key = "SECRET_KEY";
@@ -180,6 +905,15 @@ mod tests {
             "****alue"
         );
 
+        assert_eq!(
+            obfuscate_if_secret(&blacklist, "ALPHA_SECRET_KEY", "my_secret_value"),
+            "****alue"
+        );
+        assert_eq!(
+            obfuscate_if_secret(&blacklist, "NOT_A_SECRET_KEYS", "my_normal_value"),
+            "my_normal_value"
+        );
+
         let normal_key = "NORMAL_KEY";
         assert_eq!(
             obfuscate_if_secret(&blacklist, normal_key, "my_normal_value"),
@@ -211,4 +945,62 @@ mod tests {
         std::env::set_var("NETWORK", "invalid_network");
         get_network();
     }
+
+    #[test]
+    fn test_get_node_pair_prefixed() {
+        std::env::set_var("TEST_PAIR_BEACON_URL", "http://beacon:5052");
+        std::env::set_var("TEST_PAIR_EXECUTION_NODE_URL", "http://execution:8545");
+
+        let pair = get_node_pair("test-pair", &ConfigFile::default());
+
+        assert_eq!(pair.name, "test-pair");
+        assert_eq!(pair.beacon_url, "http://beacon:5052");
+        assert_eq!(pair.execution_node_url, "http://execution:8545");
+        assert_eq!(pair.engine_api_url, None);
+    }
+
+    #[test]
+    fn test_get_node_pair_from_file() {
+        std::env::set_var("FILE_PAIR_BEACON_URL", "http://env-beacon:5052");
+        let file = ConfigFile {
+            node_pairs: vec![NodePairFile {
+                name: "file-pair".to_string(),
+                beacon_client: Some("lighthouse".to_string()),
+                beacon_url: Some("http://file-beacon:5052".to_string()),
+                execution_node_url: Some("http://file-execution:8545".to_string()),
+                ..NodePairFile::default()
+            }],
+            ..ConfigFile::default()
+        };
+
+        let pair = get_node_pair("file-pair", &file);
+
+        assert_eq!(pair.beacon_client, Some(BeaconClientKind::Lighthouse));
+        assert_eq!(pair.beacon_url, "http://env-beacon:5052");
+        assert_eq!(pair.execution_node_url, "http://file-execution:8545");
+    }
+
+    #[test]
+    fn test_print() {
+        let config = EnvConfig {
+            node_pairs: vec![
+                test_pair(DEFAULT_PAIR),
+                NodePairConfig {
+                    beacon_url: "https://beacon.example.com/my-api-key".to_string(),
+                    ..test_pair("alpha")
+                },
+            ],
+            ..test_config()
+        };
+
+        let printed = config.print();
+
+        assert!(printed.contains("POLL_INTERVAL_SECS=4\n"));
+        assert!(printed.contains("NODE_PAIRS=default,alpha\n"));
+        assert!(printed.contains("BEACON_CLIENT=auto\n"));
+        assert!(printed.contains("\nBEACON_URL=****5052\n"));
+        assert!(printed.contains("ALPHA_BEACON_URL=****-key\n"));
+        assert!(!printed.contains("my-api-key"));
+        assert!(!printed.contains("MAX_CONSECUTIVE_CHECK_ERRORS"));
+    }
 }
@@ -0,0 +1,466 @@
+//! Consumes the beacon node's Server-Sent Events stream, `/eth/v1/events`, so we learn about new
+//! heads, finality and reorgs as they happen instead of a poll later. Polling keeps running and
+//! covers for the stream while it is down.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use reqwest::Client;
+use serde::Deserialize;
+use tokio::{
+    sync::Notify,
+    time::{sleep, timeout, Instant},
+};
+use tracing::{debug, info, warn};
+
+use crate::{
+    beacon_node::{deserialize_u64_from_string, BeaconNode},
+    metrics,
+    node_client::{ClientSettings, NodeClientError},
+    reorg::{Reorg, ReorgTracker},
+};
+
+const TOPICS: &str = "head,finalized_checkpoint,chain_reorg";
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Slots a stream may stay silent beyond the head event threshold before we reconnect, so a quiet
+/// but connected stream fails the head events check rather than turning into a reconnect.
+pub const IDLE_GRACE_SLOTS: u64 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct HeadEvent {
+    #[serde(deserialize_with = "deserialize_u64_from_string")]
+    pub slot: u64,
+    pub block: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct FinalizedCheckpointEvent {
+    #[serde(deserialize_with = "deserialize_u64_from_string")]
+    pub epoch: u64,
+    pub block: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ChainReorgEvent {
+    #[serde(deserialize_with = "deserialize_u64_from_string")]
+    pub slot: u64,
+    #[serde(deserialize_with = "deserialize_u64_from_string")]
+    pub depth: u64,
+    pub old_head_block: String,
+    pub new_head_block: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BeaconEvent {
+    Head(HeadEvent),
+    FinalizedCheckpoint(FinalizedCheckpointEvent),
+    ChainReorg(ChainReorgEvent),
+}
+
+impl BeaconEvent {
+    /// None for topics we don't know.
+    fn parse(event: &str, data: &str) -> Result<Option<Self>, NodeClientError> {
+        let event = match event {
+            "head" => {
+                BeaconEvent::Head(serde_json::from_str(data).map_err(NodeClientError::malformed)?)
+            }
+            "finalized_checkpoint" => BeaconEvent::FinalizedCheckpoint(
+                serde_json::from_str(data).map_err(NodeClientError::malformed)?,
+            ),
+            "chain_reorg" => BeaconEvent::ChainReorg(
+                serde_json::from_str(data).map_err(NodeClientError::malformed)?,
+            ),
+            _ => return Ok(None),
+        };
+        Ok(Some(event))
+    }
+
+    pub fn topic(&self) -> &'static str {
+        match self {
+            BeaconEvent::Head(_) => "head",
+            BeaconEvent::FinalizedCheckpoint(_) => "finalized_checkpoint",
+            BeaconEvent::ChainReorg(_) => "chain_reorg",
+        }
+    }
+}
+
+/// Splits a `text/event-stream` body into `(event, data)` messages. Chunks may end anywhere, even
+/// in the middle of a line.
+#[derive(Debug, Default)]
+struct EventStreamParser {
+    buffer: Vec<u8>,
+    event: String,
+    data: Vec<String>,
+}
+
+impl EventStreamParser {
+    fn push(&mut self, chunk: &[u8]) -> Vec<(String, String)> {
+        self.buffer.extend_from_slice(chunk);
+        let mut messages = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            // A blank line ends the message.
+            if line.is_empty() {
+                let event = std::mem::take(&mut self.event);
+                if !self.data.is_empty() {
+                    let event = if event.is_empty() {
+                        "message".to_string()
+                    } else {
+                        event
+                    };
+                    messages.push((event, self.data.join("\n")));
+                    self.data.clear();
+                }
+                continue;
+            }
+
+            // Comments, nodes send them to keep the connection alive.
+            if line.starts_with(':') {
+                continue;
+            }
+
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "event" => self.event = value.to_string(),
+                "data" => self.data.push(value.to_string()),
+                _ => {}
+            }
+        }
+        messages
+    }
+}
+
+#[derive(Debug)]
+struct LastHead {
+    event: HeadEvent,
+    received_at: Instant,
+}
+
+#[derive(Debug)]
+struct LastFinalized {
+    event: FinalizedCheckpointEvent,
+    received_at: Instant,
+}
+
+/// What the event stream of a beacon node told us last, kept up to date by [`BeaconEvents::run`].
+pub struct BeaconEvents {
+    beacon_node: Arc<BeaconNode>,
+    client: Client,
+    /// Reconnect when the stream stays silent this long.
+    max_idle: Duration,
+    connected_at: Mutex<Option<Instant>>,
+    last_head: Mutex<Option<LastHead>>,
+    last_finalized: Mutex<Option<LastFinalized>>,
+    head_notify: Notify,
+    reorgs: Option<Arc<ReorgTracker>>,
+}
+
+impl BeaconEvents {
+    pub fn new(
+        beacon_node: Arc<BeaconNode>,
+        settings: &ClientSettings,
+        max_idle: Duration,
+    ) -> Self {
+        Self {
+            beacon_node,
+            client: settings.build_streaming(),
+            max_idle,
+            connected_at: Mutex::new(None),
+            last_head: Mutex::new(None),
+            last_finalized: Mutex::new(None),
+            head_notify: Notify::new(),
+            reorgs: None,
+        }
+    }
+
+    /// Passes `chain_reorg` events on to `reorgs`.
+    pub fn with_reorg_tracker(mut self, reorgs: Arc<ReorgTracker>) -> Self {
+        self.reorgs = Some(reorgs);
+        self
+    }
+
+    pub fn beacon_node(&self) -> &BeaconNode {
+        &self.beacon_node
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected_at.lock().unwrap().is_some()
+    }
+
+    /// How long ago the stream delivered a head, reconnects don't reset it. Counts from when it
+    /// connected if it never delivered one. None while disconnected.
+    pub fn since_head(&self) -> Option<Duration> {
+        let connected_at = (*self.connected_at.lock().unwrap())?;
+        let since = match self.last_head.lock().unwrap().as_ref() {
+            Some(last_head) => last_head.received_at,
+            None => connected_at,
+        };
+        Some(since.elapsed())
+    }
+
+    pub fn last_head(&self) -> Option<HeadEvent> {
+        self.last_head
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|last_head| last_head.event.clone())
+    }
+
+    /// The last finalized checkpoint the stream delivered since it (re)connected. None while
+    /// disconnected, we may have missed newer ones in between.
+    pub fn last_finalized(&self) -> Option<FinalizedCheckpointEvent> {
+        let connected_at = (*self.connected_at.lock().unwrap())?;
+        self.last_finalized
+            .lock()
+            .unwrap()
+            .as_ref()
+            .filter(|last_finalized| last_finalized.received_at >= connected_at)
+            .map(|last_finalized| last_finalized.event.clone())
+    }
+
+    /// Resolves on the next head event.
+    pub async fn head_received(&self) {
+        self.head_notify.notified().await
+    }
+
+    pub(crate) fn set_connected(&self, connected: bool) {
+        *self.connected_at.lock().unwrap() = connected.then(Instant::now);
+        metrics::BEACON_EVENTS_CONNECTED
+            .with_label_values(&[&self.beacon_node.pair])
+            .set(i64::from(connected));
+    }
+
+    /// Consumes the stream until dropped, reconnecting with exponential backoff.
+    pub async fn run(&self) {
+        let mut backoff = MIN_BACKOFF;
+        loop {
+            let result = self.consume(&mut backoff).await;
+            self.set_connected(false);
+            match result {
+                Ok(()) => info!(
+                    "{} event stream ended, reconnecting in {}s",
+                    self.beacon_node.kind(),
+                    backoff.as_secs()
+                ),
+                Err(e) => warn!(
+                    %e,
+                    "{} event stream failed, polling only, reconnecting in {}s",
+                    self.beacon_node.kind(),
+                    backoff.as_secs()
+                ),
+            }
+            sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+
+    /// Returns once the node closes the stream.
+    async fn consume(&self, backoff: &mut Duration) -> Result<(), NodeClientError> {
+        let url = format!(
+            "{}/eth/v1/events?topics={TOPICS}",
+            self.beacon_node.node_url
+        );
+        let result: Result<(), NodeClientError> = async {
+            let res = self
+                .client
+                .get(url)
+                .header("accept", "text/event-stream")
+                .send()
+                .await?;
+            let mut res = NodeClientError::check_status(res).await?;
+            self.set_connected(true);
+            debug!("{} event stream connected", self.beacon_node.kind());
+
+            let mut parser = EventStreamParser::default();
+            loop {
+                let chunk = timeout(self.max_idle, res.chunk()).await.map_err(|_| {
+                    NodeClientError::Timeout(format!("no events for {}s", self.max_idle.as_secs()))
+                })??;
+                let Some(chunk) = chunk else {
+                    return Ok(());
+                };
+                for (event, data) in parser.push(&chunk) {
+                    // The stream works, start over should it drop.
+                    *backoff = MIN_BACKOFF;
+                    match BeaconEvent::parse(&event, &data) {
+                        Ok(Some(event)) => self.handle(event),
+                        Ok(None) => debug!(event, "ignoring unknown event"),
+                        Err(e) => warn!(%e, event, "failed to parse event"),
+                    }
+                }
+            }
+        }
+        .await;
+
+        if let Err(e) = &result {
+            metrics::REQUEST_ERRORS
+                .with_label_values(&[
+                    &self.beacon_node.pair,
+                    "beacon_node",
+                    "/eth/v1/events",
+                    e.kind(),
+                ])
+                .inc();
+        }
+
+        result
+    }
+
+    pub(crate) fn handle(&self, event: BeaconEvent) {
+        metrics::BEACON_EVENTS
+            .with_label_values(&[&self.beacon_node.pair, event.topic()])
+            .inc();
+        match event {
+            BeaconEvent::Head(head) => {
+                debug!(slot = head.slot, block = head.block, "head event");
+                *self.last_head.lock().unwrap() = Some(LastHead {
+                    event: head,
+                    received_at: Instant::now(),
+                });
+                self.head_notify.notify_waiters();
+            }
+            BeaconEvent::FinalizedCheckpoint(checkpoint) => {
+                debug!(
+                    epoch = checkpoint.epoch,
+                    block = checkpoint.block,
+                    "finalized checkpoint event"
+                );
+                *self.last_finalized.lock().unwrap() = Some(LastFinalized {
+                    event: checkpoint,
+                    received_at: Instant::now(),
+                });
+            }
+            BeaconEvent::ChainReorg(reorg) => match &self.reorgs {
+                Some(reorgs) => reorgs.record(Reorg {
+                    slot: reorg.slot,
+                    depth: reorg.depth,
+                    old_head_block: reorg.old_head_block,
+                    new_head_block: reorg.new_head_block,
+                    source: "event",
+                }),
+                None => debug!(slot = reorg.slot, depth = reorg.depth, "chain reorg event"),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parser() {
+        let mut parser = EventStreamParser::default();
+
+        assert!(parser.push(b": keep alive\n\nevent: he").is_empty());
+        assert_eq!(
+            parser.push(b"ad\r\ndata: {\"slot\":\"1\"}\r\n\r\ndata: a\ndata: b\n\n"),
+            vec![
+                ("head".to_string(), r#"{"slot":"1"}"#.to_string()),
+                ("message".to_string(), "a\nb".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_event() {
+        let event = BeaconEvent::parse(
+            "head",
+            r#"{"slot":"10","block":"0xab","state":"0xcd","epoch_transition":false}"#,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            event,
+            BeaconEvent::Head(HeadEvent {
+                slot: 10,
+                block: "0xab".to_string()
+            })
+        );
+
+        let event = BeaconEvent::parse(
+            "chain_reorg",
+            r#"{"slot":"200","depth":"2","old_head_block":"0xaa","new_head_block":"0xbb","epoch":"6"}"#,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(event.topic(), "chain_reorg");
+
+        assert_eq!(BeaconEvent::parse("block", "{}").unwrap(), None);
+        assert_eq!(
+            BeaconEvent::parse("head", "{}").unwrap_err().kind(),
+            "malformed"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_consume() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/eth/v1/events")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "topics".to_string(),
+                TOPICS.to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "text/event-stream")
+            .with_body(concat!(
+                "event: head\n",
+                "data: {\"slot\":\"10\",\"block\":\"0xab\"}\n\n",
+                ":\n\n",
+                "event: finalized_checkpoint\n",
+                "data: {\"epoch\":\"1\",\"block\":\"0xcd\",\"state\":\"0xef\"}\n\n",
+            ))
+            .create_async()
+            .await;
+
+        let beacon_node = Arc::new(BeaconNode::new(server.url()));
+        let events = BeaconEvents::new(
+            beacon_node,
+            &ClientSettings::default(),
+            Duration::from_secs(5),
+        );
+        assert_eq!(events.since_head(), None);
+
+        let mut backoff = MAX_BACKOFF;
+        events.consume(&mut backoff).await.unwrap();
+
+        assert_eq!(backoff, MIN_BACKOFF);
+        assert_eq!(events.last_head().map(|head| head.slot), Some(10));
+        assert_eq!(
+            events.last_finalized().map(|checkpoint| checkpoint.epoch),
+            Some(1)
+        );
+        assert!(events.since_head().unwrap() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_consume_error_status() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", mockito::Matcher::Any)
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let beacon_node = Arc::new(BeaconNode::new(server.url()));
+        let events = BeaconEvents::new(
+            beacon_node,
+            &ClientSettings::default(),
+            Duration::from_secs(5),
+        );
+
+        let error = events.consume(&mut MIN_BACKOFF.clone()).await.unwrap_err();
+        assert_eq!(error.kind(), "status");
+        assert!(!events.is_connected());
+    }
+}
@@ -1,56 +1,331 @@
-use reqwest::Client;
+use std::{fmt, sync::RwLock};
+
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use tracing::debug;
 
+use crate::{
+    env::DEFAULT_PAIR,
+    metrics,
+    node_client::{ClientSettings, NodeClientError},
+};
+
+fn parse_hex_u64(raw: &str) -> Result<u64, NodeClientError> {
+    u64::from_str_radix(raw.trim_start_matches("0x"), 16)
+        .map_err(|e| NodeClientError::malformed(format!("{raw} is not a hex number: {e}")))
+}
+
+fn deserialize_u64_from_hex<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s: String = serde::Deserialize::deserialize(deserializer)?;
+    parse_hex_u64(&s).map_err(serde::de::Error::custom)
+}
+
+/// The execution client, as reported by `web3_clientVersion`. Only Erigon, whose sync stages we
+/// report, and Nethermind, whose `/health` we check, are treated differently, the others are
+/// named in logs, reasons and the readiness report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionClientKind {
+    Besu,
+    Erigon,
+    Geth,
+    Nethermind,
+    Reth,
+    Unknown,
+}
+
+impl ExecutionClientKind {
+    pub fn parse(str: &str) -> Option<Self> {
+        match str.to_lowercase().as_ref() {
+            "besu" => Some(Self::Besu),
+            "erigon" => Some(Self::Erigon),
+            "geth" => Some(Self::Geth),
+            "nethermind" => Some(Self::Nethermind),
+            "reth" => Some(Self::Reth),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ExecutionClientKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutionClientKind::Besu => write!(f, "besu"),
+            ExecutionClientKind::Erigon => write!(f, "erigon"),
+            ExecutionClientKind::Geth => write!(f, "geth"),
+            ExecutionClientKind::Nethermind => write!(f, "nethermind"),
+            ExecutionClientKind::Reth => write!(f, "reth"),
+            ExecutionClientKind::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionClientVersion {
+    pub kind: ExecutionClientKind,
+    pub version: String,
+}
+
+impl ExecutionClientVersion {
+    /// Clients report something like `Geth/v1.13.5-stable-916d6a44/linux-amd64/go1.21.4`.
+    fn parse(raw: &str) -> Self {
+        let mut parts = raw.split('/');
+        let kind = parts
+            .next()
+            .and_then(ExecutionClientKind::parse)
+            .unwrap_or(ExecutionClientKind::Unknown);
+        let version = parts.next().unwrap_or_default().to_string();
+        Self { kind, version }
+    }
+}
+
+/// Erigon syncs in stages and reports the progress of each.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SyncStage {
+    pub stage_name: String,
+    #[serde(deserialize_with = "deserialize_u64_from_hex")]
+    pub block_number: u64,
+}
+
+/// Progress as reported by `eth_syncing` while the execution node is syncing.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncProgress {
+    #[serde(deserialize_with = "deserialize_u64_from_hex")]
+    pub starting_block: u64,
+    #[serde(deserialize_with = "deserialize_u64_from_hex")]
+    pub current_block: u64,
+    #[serde(deserialize_with = "deserialize_u64_from_hex")]
+    pub highest_block: u64,
+    /// Only reported by Erigon.
+    #[serde(default)]
+    pub stages: Vec<SyncStage>,
+}
+
+impl SyncProgress {
+    /// The first stage that has not caught up with the highest block yet.
+    pub fn lagging_stage(&self) -> Option<&SyncStage> {
+        self.stages
+            .iter()
+            .find(|stage| stage.block_number < self.highest_block)
+    }
+
+    pub fn blocks_remaining(&self) -> u64 {
+        self.highest_block.saturating_sub(self.current_block)
+    }
+
+    pub fn percentage(&self) -> f64 {
+        if self.highest_block == 0 {
+            return 0.0;
+        }
+        self.current_block as f64 / self.highest_block as f64 * 100.0
+    }
+}
+
+/// The few fields of a block we care about.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct BlockHeader {
+    #[serde(deserialize_with = "deserialize_u64_from_hex")]
+    pub number: u64,
+    /// Unix timestamp in seconds.
+    #[serde(deserialize_with = "deserialize_u64_from_hex")]
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionSyncStatus {
+    Synced,
+    Syncing(SyncProgress),
+}
+
+impl ExecutionSyncStatus {
+    /// `eth_syncing` returns `false` when the node is synced and a progress object otherwise.
+    fn from_result(result: &Value) -> Result<Self, NodeClientError> {
+        match result {
+            Value::Bool(false) => Ok(Self::Synced),
+            Value::Object(_) => {
+                let progress =
+                    SyncProgress::deserialize(result).map_err(NodeClientError::malformed)?;
+                Ok(Self::Syncing(progress))
+            }
+            _ => Err(NodeClientError::malformed(format!(
+                "execution_node_sync_status is neither false nor a progress object: {result}"
+            ))),
+        }
+    }
+
+    pub fn is_syncing(&self) -> bool {
+        matches!(self, Self::Syncing(_))
+    }
+}
+
+/// Nethermind's own verdict, served on `/health` when its health checks are enabled.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct NethermindHealth {
+    pub status: String,
+}
+
 pub struct ExecutionNode {
     pub node_url: String,
+    /// Name of the node pair this node belongs to, labels its metrics.
+    pub pair: String,
     client: Client,
+    kind: RwLock<ExecutionClientKind>,
 }
 
 impl ExecutionNode {
     pub fn new(node_url: String) -> Self {
+        Self::with_settings(node_url, &ClientSettings::default())
+    }
+
+    pub fn with_settings(node_url: String, settings: &ClientSettings) -> Self {
         Self {
             node_url,
-            client: Client::new(),
+            pair: DEFAULT_PAIR.to_string(),
+            client: settings.build(),
+            kind: RwLock::new(ExecutionClientKind::Unknown),
+        }
+    }
+
+    pub fn with_pair(mut self, pair: impl Into<String>) -> Self {
+        self.pair = pair.into();
+        self
+    }
+
+    /// Which execution client we're talking to, used to adapt checks and name it in logs.
+    pub fn kind(&self) -> ExecutionClientKind {
+        *self.kind.read().unwrap()
+    }
+
+    pub async fn client_version(&self) -> Result<ExecutionClientVersion, NodeClientError> {
+        let result = self.request("web3_clientVersion", json!([])).await?;
+        let raw_version = result.as_str().ok_or_else(|| {
+            NodeClientError::malformed(format!(
+                "execution_node_client_version is not string: {result}"
+            ))
+        })?;
+        Ok(ExecutionClientVersion::parse(raw_version))
+    }
+
+    /// Sets the kind from the version the node reports.
+    pub async fn detect_kind(&self) -> Result<ExecutionClientVersion, NodeClientError> {
+        let client_version = self.client_version().await?;
+        *self.kind.write().unwrap() = client_version.kind;
+        metrics::EXECUTION_CLIENT_INFO
+            .with_label_values(&[
+                &self.pair,
+                &client_version.kind.to_string(),
+                &client_version.version,
+            ])
+            .set(1);
+        Ok(client_version)
+    }
+
+    /// None when the node does not serve `/health`, Nethermind only does so when health checks
+    /// are enabled.
+    pub async fn nethermind_health(&self) -> Result<Option<NethermindHealth>, NodeClientError> {
+        let url = format!("{}/health", self.node_url.trim_end_matches('/'));
+        let res = self.client.get(url).send().await?;
+        if res.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
         }
+        // Nethermind answers 503 with the same body when it is unhealthy.
+        let health: NethermindHealth = res.json().await?;
+        Ok(Some(health))
     }
 
-    #[allow(dead_code)]
-    pub async fn syncing(&self) -> anyhow::Result<bool> {
+    /// Returns the `result` of the call, a JSON-RPC error object is turned into an error.
+    async fn request(&self, method: &str, params: Value) -> Result<Value, NodeClientError> {
         let body: String =
-            json!({ "jsonrpc":"2.0","method":"eth_syncing","params":[],"id":1 }).to_string();
-        let res = self
-            .client
-            .post(&self.node_url)
-            .header("content-type", "application/json")
-            .body(body)
-            .send()
+            json!({ "jsonrpc":"2.0","method":method,"params":params,"id":1 }).to_string();
+        let result = async {
+            let res = self
+                .client
+                .post(&self.node_url)
+                .header("content-type", "application/json")
+                .body(body)
+                .send()
+                .await?;
+            let status = res.status();
+            let text = res.text().await?;
+            let body = serde_json::from_str::<Value>(&text);
+            // Some clients answer JSON-RPC errors with a non-2xx status, the error object says
+            // more than the status does.
+            if let Some(error) = body
+                .as_ref()
+                .ok()
+                .and_then(NodeClientError::from_json_rpc_body)
+            {
+                return Err(error);
+            }
+            if !status.is_success() {
+                return Err(NodeClientError::status(status, text));
+            }
+            let body = body.map_err(NodeClientError::malformed)?;
+            match body {
+                Value::Object(mut body) if body.contains_key("result") => {
+                    Ok(body.remove("result").unwrap_or_default())
+                }
+                body => Err(NodeClientError::malformed(format!(
+                    "{method} response has no result: {body}"
+                ))),
+            }
+        }
+        .await;
+
+        if let Err(e) = &result {
+            metrics::REQUEST_ERRORS
+                .with_label_values(&[&self.pair, "execution_node", method, e.kind()])
+                .inc();
+        }
+
+        result
+    }
+
+    pub async fn syncing(&self) -> Result<ExecutionSyncStatus, NodeClientError> {
+        let result = self.request("eth_syncing", json!([])).await?;
+        ExecutionSyncStatus::from_result(&result)
+    }
+
+    /// Served the same way by every supported client, so unlike the sync status it needs no
+    /// per-client handling.
+    pub async fn peer_count(&self) -> Result<u64, NodeClientError> {
+        let result = self.request("net_peerCount", json!([])).await?;
+        let raw_peer_count = result.as_str().ok_or_else(|| {
+            NodeClientError::malformed(format!("execution_node_peer_count is not string: {result}"))
+        })?;
+        parse_hex_u64(raw_peer_count)
+    }
+
+    pub async fn chain_id(&self) -> Result<u64, NodeClientError> {
+        let result = self.request("eth_chainId", json!([])).await?;
+        let raw_chain_id = result.as_str().ok_or_else(|| {
+            NodeClientError::malformed(format!("eth_chainId is not string: {result}"))
+        })?;
+        parse_hex_u64(raw_chain_id)
+    }
+
+    pub async fn latest_block(&self) -> Result<BlockHeader, NodeClientError> {
+        let result = self
+            .request("eth_getBlockByNumber", json!(["latest", false]))
             .await?;
-        let body: Value = res.json().await?;
-        let execution_node_sync_status = body["result"]
-            .as_bool()
-            .ok_or(anyhow::anyhow!("execution_node_sync_status is not bool"))?;
-        Ok(execution_node_sync_status)
+        BlockHeader::deserialize(&result).map_err(NodeClientError::malformed)
     }
 
-    pub async fn peer_count(&self) -> anyhow::Result<u64> {
-        let body: String =
-            json!({ "jsonrpc":"2.0","method":"net_peerCount","params":[],"id":1 }).to_string();
-        let res = self
-            .client
-            .post(&self.node_url)
-            .header("content-type", "application/json")
-            .body(body)
-            .send()
+    /// None when the node doesn't know the block.
+    pub async fn block_by_hash(&self, hash: &str) -> Result<Option<BlockHeader>, NodeClientError> {
+        let result = self
+            .request("eth_getBlockByHash", json!([hash, false]))
             .await?;
-        let body: Value = res.json().await?;
-        let raw_peer_count = body["result"]
-            .as_str()
-            .ok_or(anyhow::anyhow!("execution_node_peer_count is not string"))?
-            .to_string()
-            .replace("0x", "");
-        let peer_count = u64::from_str_radix(&raw_peer_count, 16)?;
-        Ok(peer_count)
+        if result.is_null() {
+            return Ok(None);
+        }
+        BlockHeader::deserialize(&result)
+            .map(Some)
+            .map_err(NodeClientError::malformed)
     }
 
     pub async fn ping_ok(&self) -> anyhow::Result<bool> {
@@ -128,7 +403,228 @@ mod tests {
         let execution_node = ExecutionNode::new(server.url());
         let syncing = execution_node.syncing().await.unwrap();
 
-        assert!(!syncing);
+        assert_eq!(syncing, ExecutionSyncStatus::Synced);
         mock.assert_async().await;
     }
+
+    #[tokio::test]
+    async fn test_syncing_progress() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"jsonrpc":"2.0","id":1,"result":{"startingBlock":"0x0","currentBlock":"0x4b","highestBlock":"0x64","stages":[]}}"#,
+            )
+            .create_async()
+            .await;
+
+        let execution_node = ExecutionNode::new(server.url());
+        let syncing = execution_node.syncing().await.unwrap();
+
+        let ExecutionSyncStatus::Syncing(progress) = syncing else {
+            panic!("expected syncing, got {syncing:?}");
+        };
+        assert_eq!(progress.current_block, 75);
+        assert_eq!(progress.highest_block, 100);
+        assert_eq!(progress.blocks_remaining(), 25);
+        assert_eq!(progress.percentage(), 75.0);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_latest_block() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::PartialJson(
+                json!({ "method": "eth_getBlockByNumber", "params": ["latest", false] }),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"jsonrpc":"2.0","id":1,"result":{"number":"0x12a05f2","timestamp":"0x65646b1b","hash":"0xabc"}}"#,
+            )
+            .create_async()
+            .await;
+
+        let execution_node = ExecutionNode::new(server.url());
+        let block = execution_node.latest_block().await.unwrap();
+
+        assert_eq!(block.number, 19_531_250);
+        assert_eq!(block.timestamp, 1_701_079_835);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_block_by_hash() {
+        let mut server = mockito::Server::new_async().await;
+        let execution_node = ExecutionNode::new(server.url());
+
+        let mock = server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::PartialJson(
+                json!({ "method": "eth_getBlockByHash", "params": ["0xabc", false] }),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"jsonrpc":"2.0","id":1,"result":{"number":"0x12a05f2","timestamp":"0x65646b1b","hash":"0xabc"}}"#,
+            )
+            .create_async()
+            .await;
+        let block = execution_node.block_by_hash("0xabc").await.unwrap();
+        assert_eq!(block.map(|block| block.number), Some(19_531_250));
+        mock.remove_async().await;
+
+        server
+            .mock("POST", "/")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"jsonrpc":"2.0","id":1,"result":null}"#)
+            .create_async()
+            .await;
+        assert_eq!(execution_node.block_by_hash("0xdef").await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_erigon_stages() {
+        let result = json!({
+            "startingBlock": "0x0",
+            "currentBlock": "0x0",
+            "highestBlock": "0x64",
+            "stages": [
+                { "stage_name": "Snapshots", "block_number": "0x64" },
+                { "stage_name": "Headers", "block_number": "0x64" },
+                { "stage_name": "Execution", "block_number": "0x20" },
+                { "stage_name": "Finish", "block_number": "0x0" }
+            ]
+        });
+
+        let ExecutionSyncStatus::Syncing(progress) =
+            ExecutionSyncStatus::from_result(&result).unwrap()
+        else {
+            panic!("expected syncing");
+        };
+
+        let stage = progress.lagging_stage().unwrap();
+        assert_eq!(stage.stage_name, "Execution");
+        assert_eq!(stage.block_number, 32);
+    }
+
+    #[test]
+    fn test_parse_client_version() {
+        let version =
+            ExecutionClientVersion::parse("Geth/v1.13.5-stable-916d6a44/linux-amd64/go1.21.4");
+        assert_eq!(version.kind, ExecutionClientKind::Geth);
+        assert_eq!(version.version, "v1.13.5-stable-916d6a44");
+
+        let version =
+            ExecutionClientVersion::parse("Nethermind/v1.25.0+069d4d3c/linux-x64/dotnet8.0.0");
+        assert_eq!(version.kind, ExecutionClientKind::Nethermind);
+
+        let version = ExecutionClientVersion::parse("erigon/2.55.1/linux-amd64/go1.21.4");
+        assert_eq!(version.kind, ExecutionClientKind::Erigon);
+
+        let version = ExecutionClientVersion::parse("besu/v23.10.2/linux-x86_64/openjdk-java-17");
+        assert_eq!(version.kind, ExecutionClientKind::Besu);
+
+        let version =
+            ExecutionClientVersion::parse("reth/v0.1.0-alpha.13/x86_64-unknown-linux-gnu");
+        assert_eq!(version.kind, ExecutionClientKind::Reth);
+
+        let version = ExecutionClientVersion::parse("MyClient");
+        assert_eq!(version.kind, ExecutionClientKind::Unknown);
+        // Ends up as a metric label.
+        assert_eq!(version.kind.to_string(), "unknown");
+    }
+
+    #[tokio::test]
+    async fn test_detect_kind() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"jsonrpc":"2.0","id":1,"result":"Nethermind/v1.25.0/linux-x64"}"#)
+            .create_async()
+            .await;
+
+        let execution_node = ExecutionNode::new(server.url());
+        let version = execution_node.detect_kind().await.unwrap();
+
+        assert_eq!(version.kind, ExecutionClientKind::Nethermind);
+        assert_eq!(execution_node.kind(), ExecutionClientKind::Nethermind);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_nethermind_health() {
+        let mut server = mockito::Server::new_async().await;
+        let execution_node = ExecutionNode::new(server.url());
+
+        let mock = server
+            .mock("GET", "/health")
+            .with_status(503)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"status":"Unhealthy","entries":{}}"#)
+            .create_async()
+            .await;
+        let health = execution_node.nethermind_health().await.unwrap();
+        assert_eq!(health.unwrap().status, "Unhealthy");
+        mock.remove_async().await;
+
+        server
+            .mock("GET", "/health")
+            .with_status(404)
+            .create_async()
+            .await;
+        assert_eq!(execution_node.nethermind_health().await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_sync_status_rejects_other_values() {
+        assert!(ExecutionSyncStatus::from_result(&json!("0x1")).is_err());
+        assert!(ExecutionSyncStatus::from_result(&Value::Null).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_json_rpc_error() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32601,"message":"the method net_peerCount does not exist/is not available"}}"#,
+            )
+            .create_async()
+            .await;
+
+        let execution_node = ExecutionNode::new(server.url());
+        let error = execution_node.peer_count().await.unwrap_err();
+
+        assert!(matches!(
+            error,
+            NodeClientError::JsonRpc { code: -32601, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_result() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"jsonrpc":"2.0","id":1,"result":3}"#)
+            .create_async()
+            .await;
+
+        let execution_node = ExecutionNode::new(server.url());
+        let error = execution_node.peer_count().await.unwrap_err();
+
+        assert_eq!(error.kind(), "malformed");
+    }
 }
@@ -0,0 +1,444 @@
+//! The readiness of a node pair is decided by a set of independent health checks. Each check
+//! implements [`HealthCheck`], the [`CheckRegistry`] runs every registered check once per tick.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{debug, info, warn};
+
+use crate::{metrics, node_client::NodeClientError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    /// Something looks off, but not enough to take the node out of rotation.
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckOutcome {
+    pub status: CheckStatus,
+    pub reason: Option<String>,
+    /// The value the check based its verdict on, e.g. a peer count.
+    pub observed: Option<Value>,
+}
+
+impl CheckOutcome {
+    pub fn pass() -> Self {
+        Self {
+            status: CheckStatus::Pass,
+            reason: None,
+            observed: None,
+        }
+    }
+
+    pub fn warn(reason: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Warn,
+            reason: Some(reason.into()),
+            observed: None,
+        }
+    }
+
+    pub fn fail(reason: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Fail,
+            reason: Some(reason.into()),
+            observed: None,
+        }
+    }
+
+    pub fn with_observed(mut self, observed: impl Into<Value>) -> Self {
+        self.observed = Some(observed.into());
+        self
+    }
+
+    pub fn is_fail(&self) -> bool {
+        self.status == CheckStatus::Fail
+    }
+}
+
+#[async_trait]
+pub trait HealthCheck: Send + Sync {
+    /// Stable identifier, used in logs and to disable the check through config.
+    fn name(&self) -> &'static str;
+
+    /// An error means the check could not be evaluated, e.g. the node did not respond. The
+    /// registry treats it as a failing outcome and counts consecutive errors.
+    async fn evaluate(&self) -> anyhow::Result<CheckOutcome>;
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub outcome: CheckOutcome,
+    /// How many evaluations in a row, including this one, errored.
+    pub consecutive_errors: u64,
+    /// Set when the evaluation errored, the [`NodeClientError::kind`] or "other".
+    pub error_kind: Option<&'static str>,
+}
+
+pub fn is_ready(results: &[CheckResult]) -> bool {
+    results.iter().all(|result| !result.outcome.is_fail())
+}
+
+#[derive(Default)]
+pub struct CheckRegistry {
+    /// Name of the node pair the checks look at, labels the metrics.
+    pair: String,
+    checks: Vec<Box<dyn HealthCheck>>,
+    disabled: Vec<String>,
+    consecutive_errors: Mutex<HashMap<&'static str, u64>>,
+    /// How long a run may take, checks still going by then fail. None means no limit.
+    deadline: Option<Duration>,
+}
+
+impl CheckRegistry {
+    pub fn new(disabled: Vec<String>) -> Self {
+        Self {
+            disabled,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_pair(mut self, pair: impl Into<String>) -> Self {
+        self.pair = pair.into();
+        self
+    }
+
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn register(&mut self, check: impl HealthCheck + 'static) {
+        let name = check.name();
+        if self.disabled.iter().any(|disabled| disabled == name) {
+            info!(check = name, "check disabled, not registering");
+            return;
+        }
+        self.checks.push(Box::new(check));
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.checks.iter().map(|check| check.name()).collect()
+    }
+
+    /// Counts consecutive errors per check, any outcome resets the count.
+    fn track_errors(&self, name: &'static str, errored: bool) -> u64 {
+        let mut consecutive_errors = self.consecutive_errors.lock().unwrap();
+        let count = consecutive_errors.entry(name).or_default();
+        if errored {
+            *count += 1;
+        } else {
+            *count = 0;
+        }
+        *count
+    }
+
+    /// Evaluates all registered checks concurrently, so one slow node doesn't hold up the others.
+    /// Every check ends up in the results, in registration order, whether it passed or not.
+    pub async fn run(&self) -> Vec<CheckResult> {
+        let start = Instant::now();
+        let deadline = self
+            .deadline
+            .map(|deadline| tokio::time::Instant::now() + deadline);
+
+        let results = join_all(
+            self.checks
+                .iter()
+                .map(|check| self.run_check(check.as_ref(), deadline)),
+        )
+        .await;
+
+        debug!(
+            elapsed_ms = start.elapsed().as_millis() as u64,
+            "evaluated checks"
+        );
+
+        results
+    }
+
+    async fn run_check(
+        &self,
+        check: &dyn HealthCheck,
+        deadline: Option<tokio::time::Instant>,
+    ) -> CheckResult {
+        let name = check.name();
+        let start = Instant::now();
+        let evaluation = match deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline, check.evaluate())
+                .await
+                .map_err(|_| CheckError::Deadline)
+                .and_then(|evaluation| evaluation.map_err(CheckError::Check)),
+            None => check.evaluate().await.map_err(CheckError::Check),
+        };
+        let consecutive_errors = self.track_errors(name, evaluation.is_err());
+        let mut error_kind = None;
+        let outcome = evaluation.unwrap_or_else(|e| {
+            let kind = e.kind();
+            error_kind = Some(kind);
+            metrics::CHECK_ERRORS
+                .with_label_values(&[&self.pair, name, kind])
+                .inc();
+            CheckOutcome::fail(e.to_string())
+        });
+        metrics::CHECK_DURATION_SECONDS
+            .with_label_values(&[&self.pair, name])
+            .observe(start.elapsed().as_secs_f64());
+        metrics::CHECK_PASSING
+            .with_label_values(&[&self.pair, name])
+            .set(i64::from(!outcome.is_fail()));
+
+        match outcome.status {
+            CheckStatus::Pass => debug!(pair = self.pair, check = name, "check passed"),
+            CheckStatus::Warn => warn!(
+                pair = self.pair,
+                check = name,
+                reason = outcome.reason.as_deref().unwrap_or_default(),
+                "check passed with warning"
+            ),
+            CheckStatus::Fail => info!(
+                pair = self.pair,
+                check = name,
+                reason = outcome.reason.as_deref().unwrap_or_default(),
+                consecutive_errors,
+                error_kind = error_kind.unwrap_or_default(),
+                "check failed, not ready"
+            ),
+        }
+
+        CheckResult {
+            name,
+            outcome,
+            consecutive_errors,
+            error_kind,
+        }
+    }
+}
+
+/// Why a check could not be evaluated.
+enum CheckError {
+    Check(anyhow::Error),
+    /// The run's deadline passed before the check finished.
+    Deadline,
+}
+
+impl CheckError {
+    fn kind(&self) -> &'static str {
+        match self {
+            CheckError::Check(e) => e
+                .downcast_ref::<NodeClientError>()
+                .map_or("other", NodeClientError::kind),
+            CheckError::Deadline => "deadline",
+        }
+    }
+}
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckError::Check(e) => write!(f, "check errored: {e:#}"),
+            CheckError::Deadline => write!(f, "check did not finish before the deadline"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct StaticCheck {
+        name: &'static str,
+        outcome: CheckOutcome,
+    }
+
+    #[async_trait]
+    impl HealthCheck for StaticCheck {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+            Ok(self.outcome.clone())
+        }
+    }
+
+    struct ErrorCheck;
+
+    #[async_trait]
+    impl HealthCheck for ErrorCheck {
+        fn name(&self) -> &'static str {
+            "error"
+        }
+
+        async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+            Err(anyhow::anyhow!("boom"))
+        }
+    }
+
+    #[tokio::test]
+    async fn run_all_pass_is_ready() {
+        let mut registry = CheckRegistry::default();
+        registry.register(StaticCheck {
+            name: "a",
+            outcome: CheckOutcome::pass(),
+        });
+        registry.register(StaticCheck {
+            name: "b",
+            outcome: CheckOutcome::warn("meh"),
+        });
+
+        let results = registry.run().await;
+
+        assert_eq!(results.len(), 2);
+        assert!(is_ready(&results));
+    }
+
+    #[tokio::test]
+    async fn run_evaluates_checks_after_a_fail() {
+        let mut registry = CheckRegistry::default();
+        registry.register(StaticCheck {
+            name: "a",
+            outcome: CheckOutcome::fail("down"),
+        });
+        registry.register(StaticCheck {
+            name: "b",
+            outcome: CheckOutcome::pass(),
+        });
+
+        let results = registry.run().await;
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].name, "a");
+        assert_eq!(results[1].name, "b");
+        assert!(!is_ready(&results));
+    }
+
+    struct SlowCheck {
+        name: &'static str,
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl HealthCheck for SlowCheck {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+            tokio::time::sleep(self.delay).await;
+            Ok(CheckOutcome::pass())
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn run_evaluates_checks_concurrently() {
+        let mut registry = CheckRegistry::default();
+        registry.register(SlowCheck {
+            name: "a",
+            delay: Duration::from_secs(3),
+        });
+        registry.register(SlowCheck {
+            name: "b",
+            delay: Duration::from_secs(3),
+        });
+
+        let start = tokio::time::Instant::now();
+        let results = registry.run().await;
+
+        assert!(is_ready(&results));
+        assert!(start.elapsed() < Duration::from_secs(6));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn run_fails_checks_past_the_deadline() {
+        let mut registry = CheckRegistry::default().with_deadline(Duration::from_secs(5));
+        registry.register(SlowCheck {
+            name: "fast",
+            delay: Duration::from_secs(1),
+        });
+        registry.register(SlowCheck {
+            name: "hanging",
+            delay: Duration::from_secs(60),
+        });
+
+        let results = registry.run().await;
+
+        assert_eq!(results[0].outcome, CheckOutcome::pass());
+        assert!(results[1].outcome.is_fail());
+        assert_eq!(results[1].error_kind, Some("deadline"));
+    }
+
+    #[tokio::test]
+    async fn run_records_errors_as_failures() {
+        let mut registry = CheckRegistry::default();
+        registry.register(ErrorCheck);
+
+        let results = registry.run().await;
+        assert!(!is_ready(&results));
+        assert_eq!(results[0].consecutive_errors, 1);
+        assert_eq!(
+            results[0].outcome.reason.as_deref(),
+            Some("check errored: boom")
+        );
+        assert_eq!(results[0].error_kind, Some("other"));
+
+        let results = registry.run().await;
+        assert_eq!(results[0].consecutive_errors, 2);
+    }
+
+    struct UnreachableCheck;
+
+    #[async_trait]
+    impl HealthCheck for UnreachableCheck {
+        fn name(&self) -> &'static str {
+            "unreachable"
+        }
+
+        async fn evaluate(&self) -> anyhow::Result<CheckOutcome> {
+            Err(NodeClientError::Connection(
+                "connection refused".to_string(),
+            ))?
+        }
+    }
+
+    #[tokio::test]
+    async fn run_labels_errors_by_kind() {
+        let mut registry = CheckRegistry::default();
+        registry.register(UnreachableCheck);
+
+        let results = registry.run().await;
+
+        assert_eq!(results[0].error_kind, Some("connection"));
+        assert_eq!(
+            results[0].outcome.reason.as_deref(),
+            Some("check errored: connection failed: connection refused")
+        );
+    }
+
+    #[test]
+    fn register_skips_disabled() {
+        let mut registry = CheckRegistry::new(vec!["b".to_string()]);
+        registry.register(StaticCheck {
+            name: "a",
+            outcome: CheckOutcome::pass(),
+        });
+        registry.register(StaticCheck {
+            name: "b",
+            outcome: CheckOutcome::pass(),
+        });
+
+        assert_eq!(registry.names(), vec!["a"]);
+    }
+}
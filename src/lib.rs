@@ -1,4 +1,15 @@
+pub mod beacon_node;
+pub mod checks;
+pub mod clock;
+pub mod config_file;
+pub mod engine_api;
 pub mod env;
+pub mod events;
 pub mod execution_node;
-pub mod lighthouse;
+pub mod health_check;
 pub mod log;
+pub mod metrics;
+pub mod node_client;
+pub mod readiness;
+pub mod reorg;
+pub mod shutdown;
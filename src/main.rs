@@ -1,182 +1,373 @@
 mod server;
 
 use std::{
-    sync::{atomic::AtomicBool, Arc},
+    sync::Arc,
     time::{Duration, SystemTime},
 };
 
 use node_health::{
-    env::{Network, ENV_CONFIG},
-    execution_node::ExecutionNode,
-    lighthouse::Lighthouse,
+    beacon_node::BeaconNode,
+    checks::{
+        beacon_node::{
+            BeaconNodeElOfflineCheck, BeaconNodeFinalityCheck, BeaconNodeGenesisCheck,
+            BeaconNodeHeadEventsCheck, BeaconNodeHeadFreshnessCheck, BeaconNodeHeadProgressCheck,
+            BeaconNodeOptimisticCheck, BeaconNodePeerCountCheck, BeaconNodeReorgCheck,
+            BeaconNodeSyncDistanceCheck, BeaconNodeSyncingCheck,
+        },
+        cross_layer::CrossLayerHeadCheck,
+        engine_api::EngineApiCheck,
+        execution_node::{
+            ExecutionNodeChainIdCheck, ExecutionNodeHeadFreshnessCheck,
+            ExecutionNodePeerCountCheck, ExecutionNodeSyncingCheck, NethermindHealthCheck,
+        },
+    },
+    clock::SECONDS_PER_SLOT,
+    engine_api::EngineApi,
+    env::{Network, NodePairConfig, ENV_CONFIG},
+    events::{BeaconEvents, IDLE_GRACE_SLOTS},
+    execution_node::{ExecutionClientKind, ExecutionNode},
+    health_check::CheckRegistry,
     log,
+    readiness::ReadinessState,
+    reorg::ReorgTracker,
+    shutdown,
 };
-use tokio::{spawn, sync::Notify, time::sleep};
-use tracing::{debug, info};
+use tokio::{spawn, sync::watch, task::JoinSet, time::sleep};
+use tracing::{debug, error, info, info_span, warn, Instrument};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     log::init();
 
+    // Dumps the config we'd run with, e.g. to check how env vars and the config file merge.
+    if std::env::args().any(|arg| arg == "--print-config") {
+        print!("{}", ENV_CONFIG.print());
+        return ENV_CONFIG.validate();
+    }
+
     info!("starting node-health");
 
-    let shutdown_notify = Notify::new();
+    ENV_CONFIG.validate()?;
 
-    let is_ready = Arc::new(AtomicBool::new(false));
+    // Set once the server should stop, by the drain or by the server itself when it fails.
+    let stop = Arc::new(watch::channel(false).0);
 
-    spawn({
-        let is_ready = is_ready.clone();
-        async move { server::serve(is_ready, &shutdown_notify).await }
+    let pairs: Vec<Arc<ReadinessState>> = ENV_CONFIG
+        .node_pairs
+        .iter()
+        .map(|pair| {
+            Arc::new(
+                ReadinessState::new(
+                    ENV_CONFIG.max_evaluation_age,
+                    ENV_CONFIG.max_heartbeat_age,
+                    ENV_CONFIG.hysteresis(),
+                )
+                .with_pair(&pair.name),
+            )
+        })
+        .collect();
+
+    let server = spawn({
+        let pairs = pairs.clone();
+        let stop = stop.clone();
+        async move { server::serve(pairs, &stop).await }
     });
 
-    let execution_node = ExecutionNode::new(ENV_CONFIG.execution_node_url.clone());
-    let lighthouse = Lighthouse::new(ENV_CONFIG.beacon_url.clone());
+    // Each node pair gets its own monitor, one giving up only fails its own pair.
+    let mut monitors = JoinSet::new();
+    for (pair, readiness) in ENV_CONFIG.node_pairs.iter().zip(&pairs) {
+        let span = info_span!("monitor", pair = pair.name);
+        let pair = pair.clone();
+        let readiness = readiness.clone();
+        monitors.spawn(
+            async move {
+                let result = monitor(pair, readiness.clone()).await;
+                if let Err(e) = &result {
+                    error!(error = format!("{e:#}"), "monitor gave up on the node pair");
+                    readiness.mark_failed(format!("{e:#}"));
+                }
+                result
+            }
+            .instrument(span),
+        );
+    }
+
+    tokio::select! {
+        e = supervise(&mut monitors) => return Err(e),
+        _ = shutdown::signal() => {
+            shutdown::drain(&pairs, ENV_CONFIG.shutdown_drain, &stop).await;
+        }
+        _ = shutdown::stopped(&stop) => anyhow::bail!("server stopped, exiting"),
+    }
+
+    monitors.shutdown().await;
+    server.await?;
+    info!("node-health stopped");
+
+    Ok(())
+}
+
+/// Resolves with the error to exit with once no monitor is left. With a single node pair that is
+/// as soon as its monitor gives up, with several the others keep serving until they all did.
+async fn supervise(monitors: &mut JoinSet<anyhow::Result<()>>) -> anyhow::Error {
+    let mut last_error = None;
+    while let Some(result) = monitors.join_next().await {
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => last_error = Some(e),
+            // A panic is a bug, not the nodes' fault.
+            Err(e) => return e.into(),
+        }
+    }
+    last_error.unwrap_or_else(|| anyhow::anyhow!("all monitors stopped"))
+}
 
-    // It can take a long long time for the execution_node and lighthouse nodes to start responding to
+/// Waits for the nodes of a pair to start, then evaluates the checks every poll interval. Only
+/// returns when giving up.
+async fn monitor(pair: NodePairConfig, readiness: Arc<ReadinessState>) -> anyhow::Result<()> {
+    let client_settings = ENV_CONFIG.client_settings();
+    let execution_node = Arc::new(
+        ExecutionNode::with_settings(pair.execution_node_url.clone(), &client_settings)
+            .with_pair(&pair.name),
+    );
+    let beacon_node = Arc::new(
+        BeaconNode::with_settings(pair.beacon_url.clone(), &client_settings).with_pair(&pair.name),
+    );
+
+    // It can take a long long time for the execution_node and beacon nodes to start responding to
     // requests, so we wait until they are ready before we start the server.
-    const MAX_STARTUP_TIME: Duration = Duration::from_secs(60 * 15);
     let start_time = SystemTime::now();
     loop {
+        readiness.heartbeat();
+
         let execution_node_ping_ok = execution_node.ping_ok().await?;
-        let lighthouse_ping_ok = lighthouse.ping_ok().await?;
+        let beacon_node_ping_ok = beacon_node.ping_ok().await?;
 
-        if execution_node_ping_ok && lighthouse_ping_ok {
-            info!("execution_node and lighthouse are up");
+        if execution_node_ping_ok && beacon_node_ping_ok {
+            info!("execution_node and beacon_node are up");
+            readiness.mark_started();
             break;
         } else {
             debug!(
-                "execution_node_ping_ok: {}, lighthouse_ping_ok: {}",
-                execution_node_ping_ok, lighthouse_ping_ok
+                "execution_node_ping_ok: {}, beacon_node_ping_ok: {}",
+                execution_node_ping_ok, beacon_node_ping_ok
             );
         }
 
-        if start_time.elapsed()? > MAX_STARTUP_TIME {
-            anyhow::bail!("execution_node and lighthouse did not start responding in time");
+        if start_time.elapsed()? > ENV_CONFIG.max_startup_time {
+            anyhow::bail!("execution_node and beacon_node did not start responding in time");
         }
 
-        debug!("sleeping 4s until next check");
-        sleep(Duration::from_secs(4)).await;
+        debug!(
+            "sleeping {}s until next check",
+            ENV_CONFIG.poll_interval.as_secs()
+        );
+        sleep(ENV_CONFIG.poll_interval).await;
     }
 
-    loop {
-        let execution_node_syncing = execution_node.syncing().await;
-        match execution_node_syncing {
-            Ok(execution_node_syncing) => {
-                if execution_node_syncing {
-                    info!("execution_node is syncing");
-                    is_ready.store(false, std::sync::atomic::Ordering::Relaxed);
-                    sleep(Duration::from_secs(4)).await;
-                    continue;
-                } else {
-                    debug!("execution_node is not syncing");
-                }
-            }
-            Err(e) => {
-                debug!("execution_node sync check failed: {}, not ready", e);
-                is_ready.store(false, std::sync::atomic::Ordering::Relaxed);
-                sleep(Duration::from_secs(4)).await;
-                continue;
-            }
-        }
-
-        // Peer check doesn't work on goerli, so we skip it.
-        if ENV_CONFIG.network == Network::Goerli {
-            debug!("goerli network, skipping execution_node peer count check");
-        } else {
-            let min_peer_count = if ENV_CONFIG.network == Network::Mainnet {
-                5
-            } else {
-                2
-            };
-            let execution_node_peer_count = execution_node.peer_count().await;
-            match execution_node_peer_count {
-                Ok(execution_node_peer_count) => {
-                    if execution_node_peer_count < min_peer_count {
-                        info!(
-                            execution_node_peer_count,
-                            "execution_node has less than {min_peer_count} peers, not ready"
-                        );
-                        is_ready.store(false, std::sync::atomic::Ordering::Relaxed);
-                        sleep(Duration::from_secs(4)).await;
-                        continue;
-                    } else {
-                        debug!("execution_node has more than {min_peer_count} peers");
-                    }
-                }
-                Err(e) => {
-                    debug!("execution_node peer count check failed: {}, not ready", e);
-                    is_ready.store(false, std::sync::atomic::Ordering::Relaxed);
-                    sleep(Duration::from_secs(4)).await;
-                    continue;
-                }
-            }
+    match beacon_node.detect_kind(pair.beacon_client).await {
+        Ok(client_version) => {
+            info!(
+                client = %client_version.kind,
+                version = client_version.version,
+                "detected beacon client"
+            );
+            readiness.set_beacon_client(client_version.kind, client_version.version);
         }
+        Err(e) => warn!(%e, "failed to detect beacon client"),
+    }
 
-        info!("execution_node is ready");
-
-        let lighthouse_peer_counts = lighthouse.peer_counts().await?;
-        let lighthouse_peer_count = lighthouse_peer_counts.peer_count();
-        if lighthouse_peer_count < 10 {
+    match execution_node.detect_kind().await {
+        Ok(client_version) => {
             info!(
-                lighthouse_peer_count,
-                "lighthouse has less than 10 peers, not ready"
+                client = %client_version.kind,
+                version = client_version.version,
+                "detected execution client"
             );
-            is_ready.store(false, std::sync::atomic::Ordering::Relaxed);
-            sleep(Duration::from_secs(4)).await;
-            continue;
-        } else {
-            debug!("lighthouse has more than 10 peers");
+            readiness.set_execution_client(client_version.kind, client_version.version);
         }
+        Err(e) => warn!(%e, "failed to detect execution client"),
+    }
 
-        let lighthouse_sync_status = lighthouse.sync_status().await?;
+    let engine_api = match (&pair.engine_api_url, &pair.jwt_secret_path) {
+        (Some(url), Some(jwt_secret_path)) => Some(Arc::new(
+            EngineApi::from_secret_file(url.clone(), jwt_secret_path, &client_settings)?
+                .with_pair(&pair.name),
+        )),
+        _ => None,
+    };
 
-        if lighthouse_sync_status.is_syncing() {
-            info!("lighthouse is syncing, not ready");
-            is_ready.store(false, std::sync::atomic::Ordering::Relaxed);
-            sleep(Duration::from_secs(4)).await;
-            continue;
-        } else {
-            debug!("lighthouse is not syncing");
-        }
+    let reorgs = Arc::new(
+        ReorgTracker::new(Duration::from_secs(
+            ENV_CONFIG.reorg_window_slots * SECONDS_PER_SLOT,
+        ))
+        .with_pair(&pair.name),
+    );
 
-        if lighthouse_sync_status.is_optimistic() {
-            info!("lighthouse sync is optimistic, not ready");
-            is_ready.store(false, std::sync::atomic::Ordering::Relaxed);
-            sleep(Duration::from_secs(4)).await;
-            continue;
-        } else {
-            debug!("lighthouse is not optimistic");
+    // Dropping the set when the monitor ends stops the event stream with it.
+    let mut background = JoinSet::new();
+    let events = ENV_CONFIG.subscribe_events.then(|| {
+        let events = Arc::new(
+            BeaconEvents::new(
+                beacon_node.clone(),
+                &client_settings,
+                Duration::from_secs(
+                    (ENV_CONFIG.max_slots_without_head_event + IDLE_GRACE_SLOTS) * SECONDS_PER_SLOT,
+                ),
+            )
+            .with_reorg_tracker(reorgs.clone()),
+        );
+        background.spawn({
+            let events = events.clone();
+            async move { events.run().await }.in_current_span()
+        });
+        events
+    });
+
+    let registry = build_registry(
+        &pair.name,
+        execution_node,
+        beacon_node.clone(),
+        engine_api,
+        events.clone(),
+        reorgs,
+    );
+    info!(checks = ?registry.names(), "registered health checks");
+
+    loop {
+        readiness.heartbeat();
+        let results = registry.run().await;
+        readiness.record(&results);
+
+        // Errors are normally transient, only give up when told to.
+        if let Some(max_errors) = ENV_CONFIG.max_consecutive_check_errors {
+            if let Some(result) = results
+                .iter()
+                .find(|result| result.consecutive_errors >= max_errors)
+            {
+                anyhow::bail!(
+                    "check {} errored {} times in a row, giving up",
+                    result.name,
+                    result.consecutive_errors
+                );
+            }
         }
 
-        if lighthouse_sync_status.is_el_offline() {
-            info!("lighthouse says is el offline, not ready");
-            is_ready.store(false, std::sync::atomic::Ordering::Relaxed);
-            sleep(Duration::from_secs(4)).await;
-            continue;
-        } else {
-            debug!("lighthouse is not el offline");
+        if readiness.is_ready() {
+            info!("beacon node is ready for traffic");
         }
 
-        let sync_distance = lighthouse_sync_status.sync_distance();
-        // We allow to be one slot behind, this naturally happens all the time.
-        if sync_distance > 1 {
-            info!(
-                sync_distance,
-                "lighthouse sync distance is greater than 0, not ready"
-            );
-            is_ready.store(false, std::sync::atomic::Ordering::Relaxed);
-            sleep(Duration::from_secs(4)).await;
-            continue;
-        } else {
-            debug!("lighthouse sync distance is 0");
+        debug!(
+            "sleeping {}s until next check",
+            ENV_CONFIG.poll_interval.as_secs()
+        );
+        // A new head is worth evaluating right away, polling carries on when the stream is down.
+        match &events {
+            Some(events) => {
+                tokio::select! {
+                    _ = sleep(ENV_CONFIG.poll_interval) => {}
+                    _ = events.head_received() => debug!("head event, evaluating checks early"),
+                }
+            }
+            None => sleep(ENV_CONFIG.poll_interval).await,
         }
+    }
+}
 
-        info!("lighthouse is ready");
+fn build_registry(
+    pair: &str,
+    execution_node: Arc<ExecutionNode>,
+    beacon_node: Arc<BeaconNode>,
+    engine_api: Option<Arc<EngineApi>>,
+    events: Option<Arc<BeaconEvents>>,
+    reorgs: Arc<ReorgTracker>,
+) -> CheckRegistry {
+    let mut registry = CheckRegistry::new(ENV_CONFIG.disabled_checks.clone())
+        .with_pair(pair)
+        .with_deadline(ENV_CONFIG.check_timeout);
+
+    // Registered first, a node of the wrong network makes every other verdict meaningless. They run
+    // on the first poll after startup and every poll after.
+    registry.register(ExecutionNodeChainIdCheck::new(
+        execution_node.clone(),
+        ENV_CONFIG.network.clone(),
+    ));
+    registry.register(BeaconNodeGenesisCheck::new(
+        beacon_node.clone(),
+        ENV_CONFIG.network.clone(),
+    ));
+
+    registry.register(ExecutionNodeSyncingCheck::new(execution_node.clone()));
+
+    // Peer check doesn't work on goerli, so we skip it.
+    if ENV_CONFIG.network == Network::Goerli {
+        debug!("goerli network, skipping execution_node peer count check");
+    } else {
+        registry.register(ExecutionNodePeerCountCheck::new(
+            execution_node.clone(),
+            ENV_CONFIG.min_execution_node_peers,
+        ));
+    }
 
-        info!("beacon node is ready for traffic");
-        is_ready.store(true, std::sync::atomic::Ordering::Relaxed);
+    registry.register(ExecutionNodeHeadFreshnessCheck::new(
+        execution_node.clone(),
+        ENV_CONFIG.max_execution_head_lag_slots,
+    ));
+    registry.register(CrossLayerHeadCheck::new(
+        beacon_node.clone(),
+        execution_node.clone(),
+        ENV_CONFIG.max_cross_layer_block_distance,
+    ));
 
-        debug!("sleeping 4s until next check");
-        sleep(Duration::from_secs(4)).await;
+    if execution_node.kind() == ExecutionClientKind::Nethermind {
+        registry.register(NethermindHealthCheck::new(execution_node));
     }
+
+    if let Some(engine_api) = engine_api {
+        registry.register(EngineApiCheck::new(engine_api));
+    }
+
+    registry.register(BeaconNodePeerCountCheck::new(
+        beacon_node.clone(),
+        ENV_CONFIG.min_beacon_peers,
+    ));
+    registry.register(BeaconNodeSyncingCheck::new(beacon_node.clone()));
+    registry.register(BeaconNodeOptimisticCheck::new(beacon_node.clone()));
+    registry.register(BeaconNodeElOfflineCheck::new(beacon_node.clone()));
+    registry.register(BeaconNodeSyncDistanceCheck::new(
+        beacon_node.clone(),
+        ENV_CONFIG.max_sync_distance,
+    ));
+    registry.register(BeaconNodeHeadProgressCheck::new(
+        beacon_node.clone(),
+        ENV_CONFIG.max_head_stall_slots,
+    ));
+    registry.register(BeaconNodeHeadFreshnessCheck::new(
+        beacon_node.clone(),
+        ENV_CONFIG.network.clone(),
+        ENV_CONFIG.max_beacon_head_lag_slots,
+    ));
+    let reorg_check =
+        BeaconNodeReorgCheck::new(beacon_node.clone(), reorgs, ENV_CONFIG.max_reorg_depth);
+    let finality_check = BeaconNodeFinalityCheck::new(
+        beacon_node,
+        ENV_CONFIG.network.clone(),
+        ENV_CONFIG.max_finality_distance_epochs,
+    );
+    match events {
+        Some(events) => {
+            registry.register(reorg_check.with_events(events.clone()));
+            registry.register(BeaconNodeHeadEventsCheck::new(
+                events.clone(),
+                ENV_CONFIG.max_slots_without_head_event,
+            ));
+            registry.register(finality_check.with_events(events));
+        }
+        None => {
+            registry.register(reorg_check);
+            registry.register(finality_check);
+        }
+    }
+
+    registry
 }
@@ -0,0 +1,298 @@
+//! Prometheus metrics describing what node-health observes. Everything is registered in the
+//! default registry, [`encode`] renders it for the /metrics endpoint. Every metric is labelled with
+//! the node pair it describes.
+
+use std::sync::LazyLock;
+
+use prometheus::{
+    register_histogram_vec, register_int_counter_vec, register_int_gauge_vec, Encoder,
+    HistogramVec, IntCounterVec, IntGaugeVec, TextEncoder,
+};
+
+pub static READY: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_ready",
+        "1 when the node pair is ready for traffic, 0 otherwise",
+        &["pair"]
+    )
+    .unwrap()
+});
+
+pub static PHASE: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_phase",
+        "1 for the phase the node pair is in: starting, syncing, ready, degraded, failed or shutting_down",
+        &["pair", "phase"]
+    )
+    .unwrap()
+});
+
+pub static READINESS_TRANSITIONS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "node_health_readiness_transitions_total",
+        "Times readiness flipped, by the state it flipped to",
+        &["pair", "to"]
+    )
+    .unwrap()
+});
+
+pub static LAST_EVALUATION_TIMESTAMP: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_last_evaluation_timestamp_seconds",
+        "Unix time the checks were last evaluated",
+        &["pair"]
+    )
+    .unwrap()
+});
+
+pub static CHECK_PASSING: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_check_passing",
+        "1 when the check passed on its last evaluation, 0 when it failed",
+        &["pair", "check"]
+    )
+    .unwrap()
+});
+
+pub static CHECK_DURATION_SECONDS: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "node_health_check_duration_seconds",
+        "Time taken to evaluate a check",
+        &["pair", "check"]
+    )
+    .unwrap()
+});
+
+pub static CHECK_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "node_health_check_errors_total",
+        "Evaluations of a check that errored, e.g. because the node did not respond",
+        &["pair", "check", "kind"]
+    )
+    .unwrap()
+});
+
+pub static REQUEST_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "node_health_request_errors_total",
+        "Requests to a node that failed, by kind of error",
+        &["pair", "node", "endpoint", "kind"]
+    )
+    .unwrap()
+});
+
+pub static EXECUTION_CLIENT_INFO: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_execution_client_info",
+        "Always 1, labelled with the execution client and version the execution node runs",
+        &["pair", "client", "version"]
+    )
+    .unwrap()
+});
+
+pub static EXECUTION_NODE_PEER_COUNT: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_execution_node_peer_count",
+        "Peers the execution node is connected to",
+        &["pair"]
+    )
+    .unwrap()
+});
+
+pub static EXECUTION_NODE_SYNCING: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_execution_node_syncing",
+        "1 when the execution node reports it is syncing",
+        &["pair"]
+    )
+    .unwrap()
+});
+
+pub static EXECUTION_NODE_SYNC_BLOCKS_REMAINING: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_execution_node_sync_blocks_remaining",
+        "Blocks the execution node still has to sync, 0 when synced",
+        &["pair"]
+    )
+    .unwrap()
+});
+
+pub static EXECUTION_NODE_HEAD_BLOCK_NUMBER: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_execution_node_head_block_number",
+        "Number of the latest block of the execution node",
+        &["pair"]
+    )
+    .unwrap()
+});
+
+pub static EXECUTION_NODE_HEAD_AGE_SECONDS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_execution_node_head_age_seconds",
+        "Seconds between the latest block timestamp of the execution node and the wall clock",
+        &["pair"]
+    )
+    .unwrap()
+});
+
+pub static BEACON_CLIENT_INFO: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_beacon_client_info",
+        "Always 1, labelled with the consensus client and version the beacon node runs",
+        &["pair", "client", "version"]
+    )
+    .unwrap()
+});
+
+pub static BEACON_PEER_COUNT: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_beacon_peer_count",
+        "Peers the beacon node is connected to",
+        &["pair"]
+    )
+    .unwrap()
+});
+
+pub static BEACON_SYNC_DISTANCE: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_beacon_sync_distance",
+        "Slots the beacon node's head is behind the wall clock slot",
+        &["pair"]
+    )
+    .unwrap()
+});
+
+pub static BEACON_HEAD_SLOT: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_beacon_head_slot",
+        "Slot of the beacon node's head",
+        &["pair"]
+    )
+    .unwrap()
+});
+
+pub static BEACON_HEAD_LAG_SLOTS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_beacon_head_lag_slots",
+        "Slots the beacon node's head is behind the slot computed from the wall clock",
+        &["pair"]
+    )
+    .unwrap()
+});
+
+pub static BEACON_FINALIZED_EPOCH: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_beacon_finalized_epoch",
+        "Epoch of the beacon node's finalized checkpoint",
+        &["pair"]
+    )
+    .unwrap()
+});
+
+pub static BEACON_FINALITY_DISTANCE_EPOCHS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_beacon_finality_distance_epochs",
+        "Epochs the beacon node's finalized checkpoint is behind the epoch computed from the wall clock",
+        &["pair"]
+    )
+    .unwrap()
+});
+
+pub static BEACON_EVENTS_CONNECTED: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_beacon_events_connected",
+        "1 while the beacon node's event stream is connected",
+        &["pair"]
+    )
+    .unwrap()
+});
+
+pub static BEACON_EVENTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "node_health_beacon_events_total",
+        "Events received from the beacon node's event stream, by topic",
+        &["pair", "topic"]
+    )
+    .unwrap()
+});
+
+pub static BEACON_REORGS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "node_health_beacon_reorgs_total",
+        "Chain reorgs seen on the beacon node, by whether the event stream reported them or a poll noticed",
+        &["pair", "source"]
+    )
+    .unwrap()
+});
+
+pub static BEACON_REORG_DEPTH: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "node_health_beacon_reorg_depth_slots",
+        "Depth in slots of the chain reorgs seen on the beacon node",
+        &["pair"],
+        vec![1.0, 2.0, 3.0, 4.0, 8.0, 16.0, 32.0, 64.0]
+    )
+    .unwrap()
+});
+
+pub static CROSS_LAYER_BLOCK_DISTANCE: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_cross_layer_block_distance",
+        "Blocks between the beacon head's execution block and the execution node's head, 0 when the execution node knows the block",
+        &["pair"]
+    )
+    .unwrap()
+});
+
+pub static BEACON_SYNCING: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_beacon_syncing",
+        "1 when the beacon node reports it is syncing",
+        &["pair"]
+    )
+    .unwrap()
+});
+
+pub static BEACON_OPTIMISTIC: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_beacon_optimistic",
+        "1 when the beacon node reports its head is optimistic",
+        &["pair"]
+    )
+    .unwrap()
+});
+
+pub static BEACON_EL_OFFLINE: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "node_health_beacon_el_offline",
+        "1 when the beacon node reports its execution node is offline",
+        &["pair"]
+    )
+    .unwrap()
+});
+
+pub fn encode() -> anyhow::Result<String> {
+    let mut buffer = Vec::new();
+    TextEncoder::new().encode(&prometheus::gather(), &mut buffer)?;
+    Ok(String::from_utf8(buffer)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_includes_metrics() {
+        READY.with_label_values(&["test_pair"]).set(1);
+        CHECK_PASSING
+            .with_label_values(&["test_pair", "test_check"])
+            .set(0);
+
+        let text = encode().unwrap();
+
+        assert!(text.contains(r#"node_health_ready{pair="test_pair"} 1"#));
+        assert!(
+            text.contains(r#"node_health_check_passing{check="test_check",pair="test_pair"} 0"#)
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! What can go wrong talking to a node, shared by the beacon node, execution node and Engine API
+//! clients.
+
+use std::{fmt, time::Duration};
+
+use reqwest::{Client, Response, StatusCode};
+use serde_json::Value;
+
+/// Longest response body we keep in an error, nodes sometimes answer with whole HTML pages.
+const MAX_BODY_LEN: usize = 256;
+
+const USER_AGENT: &str = concat!("node-health/", env!("CARGO_PKG_VERSION"));
+
+/// How the HTTP clients talking to the nodes connect. Without timeouts a node that accepts a
+/// connection and then hangs stalls the monitor loop indefinitely.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientSettings {
+    pub connect_timeout: Duration,
+    /// Covers the whole request, from connecting until the body is read.
+    pub request_timeout: Duration,
+}
+
+impl Default for ClientSettings {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(5),
+            request_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl ClientSettings {
+    pub fn build(&self) -> Client {
+        Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.request_timeout)
+            .user_agent(USER_AGENT)
+            .build()
+            .expect("failed to build http client")
+    }
+
+    /// For long lived streams, only connecting is bounded, callers watch for idle streams.
+    pub fn build_streaming(&self) -> Client {
+        Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .user_agent(USER_AGENT)
+            .build()
+            .expect("failed to build http client")
+    }
+}
+
+#[derive(Debug)]
+pub enum NodeClientError {
+    /// We couldn't get a response at all, e.g. the connection was refused.
+    Connection(String),
+    Timeout(String),
+    /// The node rejected our credentials, e.g. a mismatched JWT secret.
+    Unauthorized(String),
+    /// The node answered with a non-2xx status.
+    Status {
+        status: u16,
+        body: String,
+    },
+    /// The node answered with a JSON-RPC error object.
+    JsonRpc {
+        code: i64,
+        message: String,
+    },
+    /// The node answered, but not with what we expected.
+    Malformed(String),
+}
+
+impl NodeClientError {
+    /// Stable label for the kind of error, used in metrics and readiness reports.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NodeClientError::Connection(_) => "connection",
+            NodeClientError::Timeout(_) => "timeout",
+            NodeClientError::Unauthorized(_) => "unauthorized",
+            NodeClientError::Status { .. } => "status",
+            NodeClientError::JsonRpc { .. } => "json_rpc",
+            NodeClientError::Malformed(_) => "malformed",
+        }
+    }
+
+    pub fn malformed(message: impl fmt::Display) -> Self {
+        NodeClientError::Malformed(message.to_string())
+    }
+
+    /// Reads the error object of a JSON-RPC response, if there is one.
+    pub fn from_json_rpc_body(body: &Value) -> Option<Self> {
+        let error = body.get("error")?;
+        Some(NodeClientError::JsonRpc {
+            code: error["code"].as_i64().unwrap_or_default(),
+            message: error["message"]
+                .as_str()
+                .map_or_else(|| error.to_string(), str::to_string),
+        })
+    }
+
+    /// Turns a non-2xx response into an error, keeping the start of the body for context.
+    pub async fn check_status(res: Response) -> Result<Response, Self> {
+        let status = res.status();
+        if status.is_success() {
+            return Ok(res);
+        }
+        let body = res.text().await.unwrap_or_default();
+        Err(Self::status(status, body))
+    }
+
+    pub fn status(status: StatusCode, body: String) -> Self {
+        let body = truncate(body);
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                NodeClientError::Unauthorized(format!("{status} {body}").trim_end().to_string())
+            }
+            status => NodeClientError::Status {
+                status: status.as_u16(),
+                body,
+            },
+        }
+    }
+}
+
+fn truncate(mut body: String) -> String {
+    if body.len() > MAX_BODY_LEN {
+        let mut end = MAX_BODY_LEN;
+        while !body.is_char_boundary(end) {
+            end -= 1;
+        }
+        body.truncate(end);
+        body.push('…');
+    }
+    body
+}
+
+impl fmt::Display for NodeClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeClientError::Connection(e) => write!(f, "connection failed: {e}"),
+            NodeClientError::Timeout(e) => write!(f, "request timed out: {e}"),
+            NodeClientError::Unauthorized(e) => write!(f, "unauthorized: {e}"),
+            NodeClientError::Status { status, body } if body.is_empty() => {
+                write!(f, "unexpected status {status}")
+            }
+            NodeClientError::Status { status, body } => {
+                write!(f, "unexpected status {status}: {body}")
+            }
+            NodeClientError::JsonRpc { code, message } => {
+                write!(f, "json-rpc error {code}: {message}")
+            }
+            NodeClientError::Malformed(e) => write!(f, "malformed response: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for NodeClientError {}
+
+impl From<reqwest::Error> for NodeClientError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            NodeClientError::Timeout(e.to_string())
+        } else if e.is_decode() {
+            NodeClientError::Malformed(e.to_string())
+        } else if let Some(status) = e.status() {
+            Self::status(status, String::new())
+        } else {
+            NodeClientError::Connection(e.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_from_json_rpc_body() {
+        let error = NodeClientError::from_json_rpc_body(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": { "code": -32601, "message": "the method net_peerCount does not exist" }
+        }))
+        .unwrap();
+
+        assert_eq!(error.kind(), "json_rpc");
+        assert_eq!(
+            error.to_string(),
+            "json-rpc error -32601: the method net_peerCount does not exist"
+        );
+        assert!(
+            NodeClientError::from_json_rpc_body(&json!({ "jsonrpc": "2.0", "result": "0x1" }))
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_connection_refused() {
+        // Nothing listens on the discard port.
+        let error: NodeClientError = reqwest::get("http://127.0.0.1:9").await.unwrap_err().into();
+
+        assert_eq!(error.kind(), "connection");
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout() {
+        // Accepts connections but never answers.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let client = ClientSettings {
+            connect_timeout: Duration::from_secs(1),
+            request_timeout: Duration::from_millis(100),
+        }
+        .build();
+
+        let error: NodeClientError = client.get(url).send().await.unwrap_err().into();
+
+        assert_eq!(error.kind(), "timeout");
+        drop(listener);
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("short".to_string()), "short");
+        assert_eq!(truncate("é".repeat(200)).chars().count(), 129);
+    }
+}
@@ -0,0 +1,561 @@
+//! Readiness state shared between the monitor loop, which records check results, and the server,
+//! which reports them.
+//!
+//! The node pair moves through these phases, each endpoint answers from the same state:
+//!
+//! ```text
+//! Starting ──> Syncing ──> Ready <──> Degraded
+//!                  any ──> Failed
+//!                  any ──> ShuttingDown
+//! ```
+//!
+//! - Starting: waiting for both nodes to respond, `/startupz` is 503.
+//! - Syncing: the nodes respond, but the checks never passed yet.
+//! - Ready: the checks pass, `/readyz` is 200.
+//! - Degraded: the checks passed before, but don't anymore.
+//! - Failed: the monitor gave up on the node pair, e.g. its nodes never started. The pair is not
+//!   live, started nor ready, other pairs carry on.
+//! - ShuttingDown: we received SIGTERM and drain, `/readyz` is 503 whatever the checks say.
+//!
+//! `/livez` is independent of the phase, it is 503 when the monitor loop stops making progress.
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::{
+    clock::unix_now,
+    health_check::{self, CheckResult, CheckStatus},
+    metrics,
+};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckReport {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub observed: Option<Value>,
+    pub consecutive_errors: u64,
+    /// What kind of error the last evaluation ran into, if it errored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<&'static str>,
+    /// Unix timestamp in seconds.
+    pub evaluated_at: u64,
+}
+
+/// A client as detected from the version it reports.
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientReport {
+    pub kind: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    #[default]
+    Starting,
+    Syncing,
+    Ready,
+    Degraded,
+    Failed,
+    ShuttingDown,
+}
+
+impl Phase {
+    const ALL: [Phase; 6] = [
+        Phase::Starting,
+        Phase::Syncing,
+        Phase::Ready,
+        Phase::Degraded,
+        Phase::Failed,
+        Phase::ShuttingDown,
+    ];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Phase::Starting => "starting",
+            Phase::Syncing => "syncing",
+            Phase::Ready => "ready",
+            Phase::Degraded => "degraded",
+            Phase::Failed => "failed",
+            Phase::ShuttingDown => "shutting_down",
+        }
+    }
+
+    /// The phase after a run of the checks decided whether we're ready.
+    fn next(self, ready: bool) -> Self {
+        match (self, ready) {
+            (Phase::ShuttingDown, _) => Phase::ShuttingDown,
+            (Phase::Failed, _) => Phase::Failed,
+            (_, true) => Phase::Ready,
+            (Phase::Ready | Phase::Degraded, false) => Phase::Degraded,
+            (Phase::Starting | Phase::Syncing, false) => Phase::Syncing,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReadinessReport {
+    pub phase: Phase,
+    pub ready: bool,
+    /// What the latest checks say, `ready` only follows once the hysteresis allows it.
+    pub checks_ready: bool,
+    /// The checks were last evaluated too long ago to trust them, we're not ready.
+    pub stale: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub beacon_client: Option<ClientReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_client: Option<ClientReport>,
+    /// Why the monitor gave up, in [`Phase::Failed`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure: Option<String>,
+    pub checks: Vec<CheckReport>,
+}
+
+/// Damps flapping, a single transient dip shouldn't take the node out of rotation and back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hysteresis {
+    /// Consecutive failing runs before a ready node turns not ready.
+    pub failures_before_not_ready: u64,
+    /// Consecutive passing runs before a not ready node turns ready.
+    pub successes_before_ready: u64,
+    /// Minimum time between two transitions.
+    pub min_dwell: Duration,
+}
+
+impl Default for Hysteresis {
+    fn default() -> Self {
+        Self {
+            failures_before_not_ready: 1,
+            successes_before_ready: 1,
+            min_dwell: Duration::ZERO,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Streaks {
+    failures: u64,
+    successes: u64,
+    transitioned_at: Option<Instant>,
+}
+
+#[derive(Debug, Default)]
+pub struct ReadinessState {
+    /// Name of the node pair, labels the metrics.
+    pair: String,
+    report: Mutex<ReadinessReport>,
+    recorded_at: Mutex<Option<Instant>>,
+    /// Last time the monitor loop showed it is making progress.
+    heartbeat_at: Mutex<Option<Instant>>,
+    streaks: Mutex<Streaks>,
+    hysteresis: Hysteresis,
+    /// None means the last recorded results are trusted forever.
+    max_evaluation_age: Option<Duration>,
+    /// None means we're live as long as the server runs.
+    max_heartbeat_age: Option<Duration>,
+}
+
+impl ReadinessState {
+    pub fn new(
+        max_evaluation_age: Duration,
+        max_heartbeat_age: Duration,
+        hysteresis: Hysteresis,
+    ) -> Self {
+        Self {
+            hysteresis,
+            max_evaluation_age: Some(max_evaluation_age),
+            max_heartbeat_age: Some(max_heartbeat_age),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_pair(mut self, pair: impl Into<String>) -> Self {
+        self.pair = pair.into();
+        self
+    }
+
+    pub fn pair(&self) -> &str {
+        &self.pair
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.report().ready
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.report().phase
+    }
+
+    /// Whether both nodes responded, i.e. we're past [`Phase::Starting`] and didn't give up.
+    pub fn is_started(&self) -> bool {
+        !matches!(self.phase(), Phase::Starting | Phase::Failed)
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.phase() == Phase::Failed
+    }
+
+    /// Called by the monitor loop on every iteration, including while waiting for the nodes to
+    /// start.
+    pub fn heartbeat(&self) {
+        *self.heartbeat_at.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// False when the monitor loop stopped beating, e.g. because it hangs or died. Before the
+    /// first beat we give it the benefit of the doubt.
+    pub fn is_live(&self) -> bool {
+        if self.is_failed() {
+            return false;
+        }
+        let heartbeat_at = *self.heartbeat_at.lock().unwrap();
+        match (self.max_heartbeat_age, heartbeat_at) {
+            (Some(max_age), Some(heartbeat_at)) => heartbeat_at.elapsed() <= max_age,
+            _ => true,
+        }
+    }
+
+    /// Both nodes responded, we move on to evaluating the checks.
+    pub fn mark_started(&self) {
+        let mut report = self.report.lock().unwrap();
+        if report.phase == Phase::Starting {
+            self.set_phase(&mut report, Phase::Syncing);
+        }
+    }
+
+    /// The monitor gave up on this node pair, it stays not ready until we restart.
+    pub fn mark_failed(&self, failure: impl ToString) {
+        let mut report = self.report.lock().unwrap();
+        report.ready = false;
+        report.failure = Some(failure.to_string());
+        metrics::READY.with_label_values(&[&self.pair]).set(0);
+        self.set_phase(&mut report, Phase::Failed);
+    }
+
+    /// Reports not ready from now on, whatever the checks say.
+    pub fn begin_shutdown(&self) {
+        let mut report = self.report.lock().unwrap();
+        report.ready = false;
+        metrics::READY.with_label_values(&[&self.pair]).set(0);
+        self.set_phase(&mut report, Phase::ShuttingDown);
+    }
+
+    fn set_phase(&self, report: &mut ReadinessReport, phase: Phase) {
+        if report.phase != phase {
+            info!(
+                pair = self.pair,
+                from = report.phase.as_str(),
+                to = phase.as_str(),
+                "phase changed"
+            );
+        }
+        report.phase = phase;
+        for candidate in Phase::ALL {
+            metrics::PHASE
+                .with_label_values(&[&self.pair, candidate.as_str()])
+                .set(i64::from(candidate == phase));
+        }
+    }
+
+    /// Whether results were recorded, but too long ago. A monitor loop stuck on a hanging node
+    /// would otherwise leave us reporting its last verdict.
+    fn is_stale(&self) -> bool {
+        let recorded_at = *self.recorded_at.lock().unwrap();
+        match (self.max_evaluation_age, recorded_at) {
+            (Some(max_age), Some(recorded_at)) => recorded_at.elapsed() > max_age,
+            _ => false,
+        }
+    }
+
+    pub fn report(&self) -> ReadinessReport {
+        let mut report = self.report.lock().unwrap().clone();
+        if self.is_stale() {
+            report.ready = false;
+            report.stale = true;
+            report.phase = report.phase.next(false);
+        }
+        report
+    }
+
+    pub fn set_beacon_client(&self, kind: impl ToString, version: impl ToString) {
+        self.report.lock().unwrap().beacon_client = Some(ClientReport {
+            kind: kind.to_string(),
+            version: version.to_string(),
+        });
+    }
+
+    pub fn set_execution_client(&self, kind: impl ToString, version: impl ToString) {
+        self.report.lock().unwrap().execution_client = Some(ClientReport {
+            kind: kind.to_string(),
+            version: version.to_string(),
+        });
+    }
+
+    /// Applies the hysteresis to the verdict of the latest run, returns whether we should be ready.
+    fn damp(&self, ready: bool, checks_ready: bool) -> bool {
+        let mut streaks = self.streaks.lock().unwrap();
+        if checks_ready {
+            streaks.successes += 1;
+            streaks.failures = 0;
+        } else {
+            streaks.failures += 1;
+            streaks.successes = 0;
+        }
+
+        let dwelled = streaks
+            .transitioned_at
+            .is_none_or(|at| at.elapsed() >= self.hysteresis.min_dwell);
+        let transition = dwelled
+            && if ready {
+                streaks.failures >= self.hysteresis.failures_before_not_ready
+            } else {
+                streaks.successes >= self.hysteresis.successes_before_ready
+            };
+
+        if !transition {
+            return ready;
+        }
+
+        streaks.transitioned_at = Some(Instant::now());
+        if ready {
+            warn!(
+                pair = self.pair,
+                failures = streaks.failures,
+                "readiness transition: not ready"
+            );
+            metrics::READINESS_TRANSITIONS
+                .with_label_values(&[&self.pair, "not_ready"])
+                .inc();
+        } else {
+            info!(
+                pair = self.pair,
+                successes = streaks.successes,
+                "readiness transition: ready"
+            );
+            metrics::READINESS_TRANSITIONS
+                .with_label_values(&[&self.pair, "ready"])
+                .inc();
+        }
+        !ready
+    }
+
+    /// Records the results of a run of the check registry. Checks that were not evaluated this run
+    /// keep their last report.
+    pub fn record(&self, results: &[CheckResult]) {
+        let evaluated_at = unix_now();
+        let mut report = self.report.lock().unwrap();
+
+        report.checks_ready = health_check::is_ready(results);
+        report.ready = self.damp(report.ready, report.checks_ready)
+            && !matches!(report.phase, Phase::Failed | Phase::ShuttingDown);
+        metrics::READY
+            .with_label_values(&[&self.pair])
+            .set(i64::from(report.ready));
+        let phase = report.phase.next(report.ready);
+        self.set_phase(&mut report, phase);
+        metrics::LAST_EVALUATION_TIMESTAMP
+            .with_label_values(&[&self.pair])
+            .set(evaluated_at as i64);
+        *self.recorded_at.lock().unwrap() = Some(Instant::now());
+
+        for result in results {
+            let check_report = CheckReport {
+                name: result.name,
+                status: result.outcome.status,
+                reason: result.outcome.reason.clone(),
+                observed: result.outcome.observed.clone(),
+                consecutive_errors: result.consecutive_errors,
+                error_kind: result.error_kind,
+                evaluated_at,
+            };
+
+            match report
+                .checks
+                .iter_mut()
+                .find(|existing| existing.name == result.name)
+            {
+                Some(existing) => *existing = check_report,
+                None => report.checks.push(check_report),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::health_check::CheckOutcome;
+
+    use super::*;
+
+    #[test]
+    fn record_keeps_unevaluated_checks() {
+        let state = ReadinessState::default();
+
+        state.record(&[
+            CheckResult {
+                name: "a",
+                outcome: CheckOutcome::pass().with_observed(12),
+                consecutive_errors: 0,
+                error_kind: None,
+            },
+            CheckResult {
+                name: "b",
+                outcome: CheckOutcome::pass(),
+                consecutive_errors: 0,
+                error_kind: None,
+            },
+        ]);
+        assert!(state.is_ready());
+
+        state.record(&[CheckResult {
+            name: "a",
+            outcome: CheckOutcome::fail("too few peers").with_observed(3),
+            consecutive_errors: 0,
+            error_kind: None,
+        }]);
+        assert!(!state.is_ready());
+
+        let report = state.report();
+        assert_eq!(report.checks.len(), 2);
+        assert_eq!(report.checks[0].status, CheckStatus::Fail);
+        assert_eq!(report.checks[0].observed, Some(Value::from(3)));
+        assert_eq!(report.checks[1].status, CheckStatus::Pass);
+    }
+
+    #[test]
+    fn stale_results_are_not_ready() {
+        let state = ReadinessState::new(
+            Duration::from_millis(10),
+            Duration::from_secs(60),
+            Hysteresis::default(),
+        );
+        state.record(&[CheckResult {
+            name: "a",
+            outcome: CheckOutcome::pass(),
+            consecutive_errors: 0,
+            error_kind: None,
+        }]);
+        assert!(state.is_ready());
+
+        std::thread::sleep(Duration::from_millis(20));
+
+        assert!(!state.is_ready());
+        assert!(state.report().stale);
+    }
+
+    fn result(passing: bool) -> CheckResult {
+        CheckResult {
+            name: "a",
+            outcome: if passing {
+                CheckOutcome::pass()
+            } else {
+                CheckOutcome::fail("down")
+            },
+            consecutive_errors: 0,
+            error_kind: None,
+        }
+    }
+
+    #[test]
+    fn hysteresis_damps_transitions() {
+        let state = ReadinessState::new(
+            Duration::from_secs(60),
+            Duration::from_secs(60),
+            Hysteresis {
+                failures_before_not_ready: 2,
+                successes_before_ready: 3,
+                min_dwell: Duration::ZERO,
+            },
+        );
+
+        state.record(&[result(true)]);
+        state.record(&[result(true)]);
+        assert!(!state.is_ready());
+        state.record(&[result(true)]);
+        assert!(state.is_ready());
+
+        state.record(&[result(false)]);
+        assert!(state.is_ready());
+        assert!(!state.report().checks_ready);
+        state.record(&[result(true)]);
+        state.record(&[result(false)]);
+        assert!(state.is_ready());
+        state.record(&[result(false)]);
+        assert!(!state.is_ready());
+    }
+
+    #[test]
+    fn hysteresis_respects_min_dwell() {
+        let state = ReadinessState::new(
+            Duration::from_secs(60),
+            Duration::from_secs(60),
+            Hysteresis {
+                min_dwell: Duration::from_secs(60),
+                ..Hysteresis::default()
+            },
+        );
+
+        state.record(&[result(true)]);
+        assert!(state.is_ready());
+        state.record(&[result(false)]);
+        assert!(state.is_ready());
+    }
+
+    #[test]
+    fn phases() {
+        let state = ReadinessState::default();
+        assert_eq!(state.phase(), Phase::Starting);
+        assert!(!state.is_started());
+
+        state.mark_started();
+        assert_eq!(state.phase(), Phase::Syncing);
+        assert!(state.is_started());
+
+        state.record(&[result(false)]);
+        assert_eq!(state.phase(), Phase::Syncing);
+        state.record(&[result(true)]);
+        assert_eq!(state.phase(), Phase::Ready);
+        state.record(&[result(false)]);
+        assert_eq!(state.phase(), Phase::Degraded);
+        state.record(&[result(true)]);
+        assert_eq!(state.phase(), Phase::Ready);
+
+        state.heartbeat();
+        state.mark_failed("nodes did not start");
+        assert_eq!(state.phase(), Phase::Failed);
+        assert!(!state.is_ready());
+        assert!(!state.is_started());
+        assert!(!state.is_live());
+        assert_eq!(
+            state.report().failure.as_deref(),
+            Some("nodes did not start")
+        );
+        state.record(&[result(true)]);
+        assert_eq!(state.phase(), Phase::Failed);
+        assert!(!state.is_ready());
+    }
+
+    #[test]
+    fn missed_heartbeats_are_not_live() {
+        let state = ReadinessState::new(
+            Duration::from_secs(60),
+            Duration::from_millis(10),
+            Hysteresis::default(),
+        );
+        assert!(state.is_live());
+
+        state.heartbeat();
+        assert!(state.is_live());
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(!state.is_live());
+    }
+}
@@ -0,0 +1,128 @@
+//! Chain reorganisations seen on a beacon node, reported by its event stream or noticed between
+//! polls. Deep reorgs on our nodes point at peering or fork choice trouble.
+
+use std::{sync::Mutex, time::Duration};
+
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+use crate::{env::DEFAULT_PAIR, metrics};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reorg {
+    /// Slot of the new head.
+    pub slot: u64,
+    /// Slots between the old head and the common ancestor of the old and new head.
+    pub depth: u64,
+    pub old_head_block: String,
+    pub new_head_block: String,
+    /// Where we learned about it, `event` or `poll`.
+    pub source: &'static str,
+}
+
+#[derive(Debug)]
+struct SeenReorg {
+    reorg: Reorg,
+    seen_at: Instant,
+}
+
+/// Logs and counts reorgs, and remembers the deepest recent one for readiness.
+#[derive(Debug)]
+pub struct ReorgTracker {
+    pair: String,
+    /// Reorgs older than this are forgotten.
+    window: Duration,
+    recent: Mutex<Vec<SeenReorg>>,
+}
+
+impl ReorgTracker {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            pair: DEFAULT_PAIR.to_string(),
+            window,
+            recent: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn with_pair(mut self, pair: impl Into<String>) -> Self {
+        self.pair = pair.into();
+        self
+    }
+
+    pub fn record(&self, reorg: Reorg) {
+        if reorg.depth > 1 {
+            warn!(
+                slot = reorg.slot,
+                depth = reorg.depth,
+                old_head_block = reorg.old_head_block,
+                new_head_block = reorg.new_head_block,
+                source = reorg.source,
+                "chain reorg"
+            );
+        } else {
+            info!(
+                slot = reorg.slot,
+                depth = reorg.depth,
+                old_head_block = reorg.old_head_block,
+                new_head_block = reorg.new_head_block,
+                source = reorg.source,
+                "chain reorg"
+            );
+        }
+        metrics::BEACON_REORGS
+            .with_label_values(&[&self.pair, reorg.source])
+            .inc();
+        metrics::BEACON_REORG_DEPTH
+            .with_label_values(&[&self.pair])
+            .observe(reorg.depth as f64);
+
+        let mut recent = self.recent.lock().unwrap();
+        recent.retain(|seen| seen.seen_at.elapsed() <= self.window);
+        recent.push(SeenReorg {
+            reorg,
+            seen_at: Instant::now(),
+        });
+    }
+
+    /// The deepest reorg within the window, and how long ago it was seen.
+    pub fn deepest_recent(&self) -> Option<(Reorg, Duration)> {
+        let mut recent = self.recent.lock().unwrap();
+        recent.retain(|seen| seen.seen_at.elapsed() <= self.window);
+        recent
+            .iter()
+            .max_by_key(|seen| seen.reorg.depth)
+            .map(|seen| (seen.reorg.clone(), seen.seen_at.elapsed()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reorg(depth: u64) -> Reorg {
+        Reorg {
+            slot: 100,
+            depth,
+            old_head_block: "0xaa".to_string(),
+            new_head_block: "0xbb".to_string(),
+            source: "event",
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_deepest_recent() {
+        let tracker = ReorgTracker::new(Duration::from_secs(60));
+        assert_eq!(tracker.deepest_recent(), None);
+
+        tracker.record(reorg(3));
+        tokio::time::advance(Duration::from_secs(30)).await;
+        tracker.record(reorg(1));
+        assert_eq!(tracker.deepest_recent().unwrap().0.depth, 3);
+
+        tokio::time::advance(Duration::from_secs(31)).await;
+        assert_eq!(tracker.deepest_recent().unwrap().0.depth, 1);
+
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert_eq!(tracker.deepest_recent(), None);
+    }
+}
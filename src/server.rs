@@ -1,35 +1,203 @@
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
-};
+use std::{collections::HashMap, sync::Arc};
 
 use anyhow::Context;
-use axum::{extract::State, response::IntoResponse, routing::get, Router, Server};
-use node_health::env::{self, ENV_CONFIG};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router, Server,
+};
+use node_health::{
+    env::{self, ENV_CONFIG},
+    metrics,
+    readiness::{ReadinessReport, ReadinessState},
+    shutdown,
+};
 use reqwest::StatusCode;
-use tokio::sync::Notify;
+use serde::Serialize;
+use tokio::sync::watch;
 use tracing::{error, info};
 
 #[derive(Clone)]
 pub struct AppState {
-    pub is_ready: Arc<AtomicBool>,
+    /// One per monitored node pair, in configured order.
+    pub pairs: Arc<Vec<Arc<ReadinessState>>>,
+}
+
+impl AppState {
+    fn pair(&self, name: &str) -> Option<&ReadinessState> {
+        self.pairs
+            .iter()
+            .find(|readiness| readiness.pair() == name)
+            .map(|readiness| readiness.as_ref())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct PairStatus {
+    name: String,
+    live: bool,
+    #[serde(flatten)]
+    report: ReadinessReport,
+}
+
+/// Every node pair at a glance, `ready` is true when all of them are.
+#[derive(Debug, Serialize)]
+struct Status {
+    ready: bool,
+    pairs: Vec<PairStatus>,
 }
 
-async fn is_ready_handler(state: State<AppState>) -> impl IntoResponse {
-    if state.is_ready.load(Ordering::Relaxed) {
+impl Status {
+    fn new(pairs: &[Arc<ReadinessState>]) -> Self {
+        let pairs: Vec<PairStatus> = pairs
+            .iter()
+            .map(|readiness| PairStatus {
+                name: readiness.pair().to_string(),
+                live: readiness.is_live(),
+                report: readiness.report(),
+            })
+            .collect();
+        Self {
+            ready: pairs.iter().all(|pair| pair.report.ready),
+            pairs,
+        }
+    }
+}
+
+fn wants_json(params: &HashMap<String, String>, headers: &HeaderMap) -> bool {
+    let verbose = params
+        .get("verbose")
+        .is_some_and(|verbose| verbose != "false" && verbose != "0");
+    let accepts_json = headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("application/json"));
+    verbose || accepts_json
+}
+
+fn status_code(ok: bool) -> StatusCode {
+    if ok {
         StatusCode::OK
     } else {
         StatusCode::SERVICE_UNAVAILABLE
     }
 }
 
-pub async fn serve(is_ready: Arc<AtomicBool>, shutdown_notify: &Notify) {
+fn unknown_pair(name: &str) -> Response {
+    (StatusCode::NOT_FOUND, format!("unknown node pair {name}")).into_response()
+}
+
+fn ready_response(report: ReadinessReport, json: bool) -> Response {
+    let status_code = status_code(report.ready);
+    if json {
+        (status_code, Json(report)).into_response()
+    } else {
+        status_code.into_response()
+    }
+}
+
+/// Ready when every node pair is. With a single pair the JSON body is its report, with several
+/// it is the same as `/status`.
+async fn is_ready_handler(
+    state: State<AppState>,
+    Query(params): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Response {
+    let json = wants_json(&params, &headers);
+    if let [readiness] = state.pairs.as_slice() {
+        return ready_response(readiness.report(), json);
+    }
+
+    let status = Status::new(&state.pairs);
+    let status_code = status_code(status.ready);
+    if json {
+        (status_code, Json(status)).into_response()
+    } else {
+        status_code.into_response()
+    }
+}
+
+async fn is_pair_ready_handler(
+    state: State<AppState>,
+    Path(name): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Response {
+    match state.pair(&name) {
+        Some(readiness) => ready_response(readiness.report(), wants_json(&params, &headers)),
+        None => unknown_pair(&name),
+    }
+}
+
+/// 503 once a monitor loop stops making progress, so Kubernetes restarts us. Pairs whose monitor
+/// gave up don't count, restarting would take the healthy pairs down with them.
+async fn is_live_handler(state: State<AppState>) -> StatusCode {
+    status_code(
+        state
+            .pairs
+            .iter()
+            .filter(|readiness| !readiness.is_failed())
+            .all(|readiness| readiness.is_live()),
+    )
+}
+
+async fn is_pair_live_handler(state: State<AppState>, Path(name): Path<String>) -> Response {
+    match state.pair(&name) {
+        Some(readiness) => status_code(readiness.is_live()).into_response(),
+        None => unknown_pair(&name),
+    }
+}
+
+/// 503 while we wait for the nodes to start responding.
+async fn is_started_handler(state: State<AppState>) -> StatusCode {
+    status_code(
+        state
+            .pairs
+            .iter()
+            .filter(|readiness| !readiness.is_failed())
+            .all(|readiness| readiness.is_started()),
+    )
+}
+
+async fn is_pair_started_handler(state: State<AppState>, Path(name): Path<String>) -> Response {
+    match state.pair(&name) {
+        Some(readiness) => status_code(readiness.is_started()).into_response(),
+        None => unknown_pair(&name),
+    }
+}
+
+async fn status_handler(state: State<AppState>) -> Json<Status> {
+    Json(Status::new(&state.pairs))
+}
+
+async fn metrics_handler() -> Response {
+    match metrics::encode() {
+        Ok(text) => ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], text).into_response(),
+        Err(e) => {
+            error!(%e, "failed to encode metrics");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Serves until `stop` is set, and sets it when the server fails.
+pub async fn serve(pairs: Vec<Arc<ReadinessState>>, stop: &watch::Sender<bool>) {
     let result = {
-        let state = AppState { is_ready };
+        let state = AppState {
+            pairs: Arc::new(pairs),
+        };
 
         let app = Router::new()
-            .route("/livez", get(|| async { StatusCode::OK }))
+            .route("/livez", get(is_live_handler))
+            .route("/livez/:name", get(is_pair_live_handler))
             .route("/readyz", get(is_ready_handler))
+            .route("/readyz/:name", get(is_pair_ready_handler))
+            .route("/startupz", get(is_started_handler))
+            .route("/startupz/:name", get(is_pair_started_handler))
+            .route("/status", get(status_handler))
+            .route("/metrics", get(metrics_handler))
             .with_state(state);
 
         // Developing locally we don't want to expose our server to the world.
@@ -48,9 +216,7 @@ pub async fn serve(is_ready: Arc<AtomicBool>, shutdown_notify: &Notify) {
 
         Server::bind(&socket_addr)
             .serve(app.into_make_service())
-            .with_graceful_shutdown(async {
-                shutdown_notify.notified().await;
-            })
+            .with_graceful_shutdown(shutdown::stopped(stop))
             .await
             .context("running server")
     };
@@ -59,7 +225,90 @@ pub async fn serve(is_ready: Arc<AtomicBool>, shutdown_notify: &Notify) {
         Ok(_) => info!("server thread exiting"),
         Err(e) => {
             error!(%e, "server thread hit error, exiting");
-            shutdown_notify.notify_waiters();
+            stop.send_replace(true);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::http::HeaderValue;
+    use node_health::health_check::{CheckOutcome, CheckResult};
+
+    use super::*;
+
+    #[test]
+    fn test_wants_json() {
+        let mut params = HashMap::new();
+        let mut headers = HeaderMap::new();
+        assert!(!wants_json(&params, &headers));
+
+        params.insert("verbose".to_string(), String::new());
+        assert!(wants_json(&params, &headers));
+
+        params.insert("verbose".to_string(), "false".to_string());
+        assert!(!wants_json(&params, &headers));
+
+        headers.insert(header::ACCEPT, HeaderValue::from_static("application/json"));
+        assert!(wants_json(&params, &headers));
+    }
+
+    #[test]
+    fn test_status() {
+        let alpha = Arc::new(ReadinessState::default().with_pair("alpha"));
+        let beta = Arc::new(ReadinessState::default().with_pair("beta"));
+        let state = AppState {
+            pairs: Arc::new(vec![alpha.clone(), beta]),
+        };
+        assert!(state.pair("alpha").is_some());
+        assert!(state.pair("gamma").is_none());
+
+        alpha.record(&[]);
+        let status = Status::new(&state.pairs);
+
+        assert!(!status.ready);
+        assert_eq!(status.pairs[0].name, "alpha");
+        assert!(status.pairs[0].report.ready);
+        assert!(!status.pairs[1].report.ready);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_not_ready_while_draining() {
+        let readiness = Arc::new(ReadinessState::default());
+        readiness.record(&[CheckResult {
+            name: "a",
+            outcome: CheckOutcome::pass(),
+            consecutive_errors: 0,
+            error_kind: None,
+        }]);
+        let state = AppState {
+            pairs: Arc::new(vec![readiness.clone()]),
+        };
+        let ready_status = || {
+            is_ready_handler(
+                State(state.clone()),
+                Query(HashMap::new()),
+                HeaderMap::new(),
+            )
+        };
+        assert_eq!(ready_status().await.status(), StatusCode::OK);
+
+        let stop = Arc::new(watch::channel(false).0);
+        let draining = tokio::spawn({
+            let stop = stop.clone();
+            async move { shutdown::drain(&[readiness], Duration::from_secs(10), &stop).await }
+        });
+
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        assert_eq!(
+            ready_status().await.status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert!(!*stop.borrow());
+
+        draining.await.unwrap();
+        assert!(*stop.borrow());
+    }
+}
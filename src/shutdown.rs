@@ -0,0 +1,105 @@
+//! Graceful shutdown. Kubernetes keeps sending traffic for a while after it sends SIGTERM, until
+//! the endpoints are updated, so we first report not ready and keep serving for a drain period
+//! before stopping.
+
+use std::{sync::Arc, time::Duration};
+
+use tokio::{signal, sync::watch, time::sleep};
+use tracing::info;
+
+use crate::readiness::ReadinessState;
+
+/// Resolves on the first SIGTERM or SIGINT.
+pub async fn signal() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("failed to install SIGINT handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("received SIGINT"),
+        _ = terminate => info!("received SIGTERM"),
+    }
+}
+
+/// Reports every node pair not ready straight away, waits out the drain period, then tells the
+/// server to stop by setting `stop`.
+pub async fn drain(
+    pairs: &[Arc<ReadinessState>],
+    drain_period: Duration,
+    stop: &watch::Sender<bool>,
+) {
+    for readiness in pairs {
+        readiness.begin_shutdown();
+    }
+    info!(
+        drain_secs = drain_period.as_secs(),
+        "shutting down, draining before stopping the server"
+    );
+    sleep(drain_period).await;
+    info!("drain period over, stopping the server");
+    stop.send_replace(true);
+}
+
+/// Resolves once `stop` is set, also when it was set before we started waiting.
+pub async fn stopped(stop: &watch::Sender<bool>) {
+    // The sender is alive while we hold a reference to it, so this can't fail.
+    let _ = stop.subscribe().wait_for(|stopped| *stopped).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        health_check::{CheckOutcome, CheckResult},
+        readiness::Phase,
+    };
+
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain() {
+        let readiness = Arc::new(ReadinessState::default());
+        readiness.record(&[CheckResult {
+            name: "a",
+            outcome: CheckOutcome::pass(),
+            consecutive_errors: 0,
+            error_kind: None,
+        }]);
+        assert!(readiness.is_ready());
+
+        let stop = Arc::new(watch::channel(false).0);
+        let server = tokio::spawn({
+            let stop = stop.clone();
+            async move { stopped(&stop).await }
+        });
+
+        let draining = tokio::spawn({
+            let pairs = vec![readiness.clone()];
+            let stop = stop.clone();
+            async move { drain(&pairs, Duration::from_secs(10), &stop).await }
+        });
+
+        sleep(Duration::from_secs(1)).await;
+        assert!(!readiness.is_ready());
+        assert_eq!(readiness.phase(), Phase::ShuttingDown);
+        assert!(!server.is_finished());
+
+        draining.await.unwrap();
+        server.await.unwrap();
+
+        // Whoever starts waiting late still sees the stop.
+        stopped(&stop).await;
+    }
+}